@@ -1,13 +1,63 @@
+use futures_util::StreamExt;
+use sha2::{Digest, Sha256};
 use std::env;
-use std::fs::{self, File};
-use std::io::Write;
-use std::path::PathBuf;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::future::Future;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-fn get_platform_info() -> Result<(String, String, String, String), Box<dyn std::error::Error>> {
-    // Retrieve the current operating system and architecture
-    let os = env::consts::OS;
-    let arch = env::consts::ARCH;
+const DOWNLOAD_ATTEMPTS: u32 = 3;
+const BYTES_PER_MB: u64 = 1024 * 1024;
+/// How often download progress is printed to the build log
+const PROGRESS_INTERVAL_BYTES: u64 = 20 * BYTES_PER_MB;
+/// How often `download-progress.json` is rewritten
+const PROGRESS_FILE_INTERVAL_BYTES: u64 = BYTES_PER_MB;
+/// Audit record of where each bundled binary came from, keyed by file name
+const META_FILE: &str = "ollama.meta.json";
+/// Redirects followed per request before giving up
+const MAX_REDIRECTS: usize = 10;
 
+/// Raised when a downloaded binary doesn't match its published SHA-256.
+/// Unlike network failures this always aborts the build.
+#[derive(Debug)]
+struct ChecksumMismatch {
+    filename: String,
+    expected: String,
+    actual: String,
+}
+
+impl fmt::Display for ChecksumMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "checksum mismatch for {}: expected {}, got {}",
+            self.filename, self.expected, self.actual
+        )
+    }
+}
+
+impl std::error::Error for ChecksumMismatch {}
+
+/// A download failure worth retrying: connection problems, timeouts, 5xx
+/// responses and truncated bodies. Anything else (e.g. a 404 for a wrong
+/// version or filename) fails immediately.
+#[derive(Debug)]
+struct TransientError(String);
+
+impl fmt::Display for TransientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for TransientError {}
+
+fn get_platform_info(
+    os: &str,
+    arch: &str,
+) -> Result<(String, String, String, String), Box<dyn std::error::Error>> {
     // Determine os_name, arch_name, filename, and download_filename based on OS and architecture
     let (os_name, arch_name, filename, download_filename) = match (os, arch) {
         ("macos", "aarch64") => (
@@ -34,6 +84,19 @@ fn get_platform_info() -> Result<(String, String, String, String), Box<dyn std::
             "ollama-x86_64-unknown-linux",
             "ollama-linux-amd64"
         ),
+        ("windows", "x86_64") => (
+            "windows",
+            "x86_64",
+            "ollama.exe",
+            "ollama.exe"
+        ),
+        ("windows", "aarch64") => (
+            "windows",
+            "aarch64",
+            "ollama-aarch64-pc-windows.exe",
+            "ollama-windows-arm64.exe"
+        ),
+        // Other Windows architectures fall back to the x86_64 build
         ("windows", _) => (
             "windows",
             arch,
@@ -51,55 +114,597 @@ fn get_platform_info() -> Result<(String, String, String, String), Box<dyn std::
     ))
 }
 
-fn download_ollama() -> Result<(), Box<dyn std::error::Error>> {
+const DEFAULT_RELEASES_URL: &str = "https://github.com/ollama/ollama/releases";
+
+/// Resolves the release download prefix, honoring a pinned `OLLAMA_VERSION`
+/// and an `OLLAMA_DOWNLOAD_BASE_URL` mirror in place of GitHub releases.
+/// Returns the prefix along with the version label used in the build log.
+fn get_release_url() -> Result<(String, String), Box<dyn std::error::Error>> {
+    let base_url = match env::var("OLLAMA_DOWNLOAD_BASE_URL") {
+        Ok(base_url) if !base_url.trim().is_empty() => {
+            let base_url = base_url.trim().trim_end_matches('/').to_string();
+            let parsed = reqwest::Url::parse(&base_url)
+                .map_err(|e| format!("Invalid OLLAMA_DOWNLOAD_BASE_URL '{}': {}", base_url, e))?;
+            if !matches!(parsed.scheme(), "http" | "https") || parsed.host_str().is_none() {
+                return Err(format!(
+                    "Invalid OLLAMA_DOWNLOAD_BASE_URL '{}': expected an http(s) URL with a host",
+                    base_url
+                )
+                .into());
+            }
+            println!("Using Ollama download mirror {}", base_url);
+            base_url
+        }
+        _ => DEFAULT_RELEASES_URL.to_string(),
+    };
+
+    Ok(match env::var("OLLAMA_VERSION") {
+        Ok(version) if !version.trim().is_empty() => {
+            let version = version.trim().to_string();
+            (format!("{}/download/{}", base_url, version), version)
+        }
+        _ => (format!("{}/latest/download", base_url), "latest".to_string()),
+    })
+}
+
+/// Proxy variables honored for the download, in priority order.
+const PROXY_ENV_VARS: [&str; 5] = [
+    "OLLAMA_DOWNLOAD_PROXY",
+    "HTTPS_PROXY",
+    "https_proxy",
+    "HTTP_PROXY",
+    "http_proxy",
+];
+
+/// Builds the download client. `OLLAMA_DOWNLOAD_PROXY` overrides the proxy
+/// reqwest would otherwise pick up from `HTTPS_PROXY`/`HTTP_PROXY`; `NO_PROXY`
+/// is respected either way. Also returns the name of the variable the proxy
+/// came from so connection errors can point at it.
+fn build_download_client(
+    redirect: reqwest::redirect::Policy,
+) -> Result<(reqwest::Client, Option<&'static str>), Box<dyn std::error::Error>> {
+    let proxy_source = PROXY_ENV_VARS
+        .into_iter()
+        .find(|name| env::var(name).is_ok_and(|value| !value.trim().is_empty()));
+
+    let mut builder = reqwest::Client::builder().redirect(redirect);
+    if proxy_source == Some("OLLAMA_DOWNLOAD_PROXY") {
+        let proxy_url = env::var("OLLAMA_DOWNLOAD_PROXY")?;
+        let proxy = reqwest::Proxy::all(proxy_url.trim())
+            .map_err(|e| format!("Invalid OLLAMA_DOWNLOAD_PROXY: {}", e))?
+            .no_proxy(reqwest::NoProxy::from_env());
+        builder = builder.proxy(proxy);
+    }
+
+    Ok((builder.build().map_err(|e| e.to_string())?, proxy_source))
+}
+
+/// Sends a request, turning proxy connection failures into an actionable message.
+async fn send_request(
+    request: reqwest::RequestBuilder,
+    url: &str,
+    proxy_source: Option<&str>,
+) -> Result<reqwest::Response, Box<dyn std::error::Error>> {
+    request.send().await.map_err(|e| {
+        let message = match proxy_source {
+            Some(source) if e.is_connect() => format!(
+                "Could not connect to {} through the proxy set in {} (check the proxy address and credentials): {}",
+                url, source, e
+            ),
+            _ if e.is_redirect() => format!(
+                "Gave up on {} after more than {} redirects: {}",
+                url, MAX_REDIRECTS, e
+            ),
+            _ => e.to_string(),
+        };
+        if e.is_connect() || e.is_timeout() || e.is_request() {
+            TransientError(message).into()
+        } else {
+            message.into()
+        }
+    })
+}
+
+/// Looks up the checksum for `download_filename` in the release's `sha256sum.txt`.
+async fn fetch_expected_checksum(
+    client: &reqwest::Client,
+    proxy_source: Option<&str>,
+    release_url: &str,
+    download_filename: &str,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let url = format!("{}/sha256sum.txt", release_url);
+    let response = send_request(client.get(&url), &url, proxy_source).await?;
+    let status = response.status();
+    if !status.is_success() {
+        let message = format!("Failed to fetch checksums from {}: {}", url, status);
+        if status.is_server_error() {
+            return Err(TransientError(message).into());
+        }
+        return Err(message.into());
+    }
+    let body = response
+        .text()
+        .await
+        .map_err(|e| TransientError(format!("Failed to read checksums from {}: {}", url, e)))?;
+
+    // Lines look like "<sha256>  ./<filename>"
+    body.lines()
+        .filter_map(|line| line.split_once(char::is_whitespace))
+        .find(|(_, name)| name.trim().trim_start_matches("./") == download_filename)
+        .map(|(hash, _)| hash.to_lowercase())
+        .ok_or_else(|| format!("No published checksum for {}", download_filename).into())
+}
+
+/// Runs `attempt` until it succeeds, retrying `TransientError`s with
+/// exponential backoff up to `DOWNLOAD_ATTEMPTS` times in all. `what` names
+/// the step in the build log.
+async fn with_retries<T, F, Fut>(
+    what: &str,
+    mut attempt: F,
+) -> Result<T, Box<dyn std::error::Error>>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, Box<dyn std::error::Error>>>,
+{
+    let mut tries = 1;
+    loop {
+        match attempt().await {
+            Ok(value) => return Ok(value),
+            Err(e) if tries < DOWNLOAD_ATTEMPTS && e.is::<TransientError>() => {
+                let delay = Duration::from_secs(2u64.pow(tries));
+                println!(
+                    "cargo:warning={} attempt {}/{} failed ({}), retrying in {}s",
+                    what,
+                    tries,
+                    DOWNLOAD_ATTEMPTS,
+                    e,
+                    delay.as_secs()
+                );
+                tokio::time::sleep(delay).await;
+                tries += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+fn sha256_file(path: &Path) -> io::Result<String> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut File::open(path)?, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Checks that the file starts with the executable magic for `os_name`
+/// (ELF, Mach-O or PE), so error pages never get bundled as a binary.
+fn has_executable_magic(path: &Path, os_name: &str) -> io::Result<bool> {
+    let mut magic = Vec::with_capacity(4);
+    File::open(path)?.take(4).read_to_end(&mut magic)?;
+
+    Ok(match os_name {
+        "linux" => magic == b"\x7fELF",
+        "darwin" => matches!(
+            magic.as_slice(),
+            [0xfe, 0xed, 0xfa, 0xce | 0xcf] | [0xce | 0xcf, 0xfa, 0xed, 0xfe] | [0xca, 0xfe, 0xba, 0xbe]
+        ),
+        "windows" => magic.starts_with(b"MZ"),
+        _ => true,
+    })
+}
+
+/// Streams `url` into `part_path`, resuming from the end of an existing
+/// partial file with a `Range` request. Succeeds only once the file holds
+/// the complete body, as reported by the server's `Content-Length`, and
+/// that body looks like an executable for `os_name`.
+async fn download_to_part(
+    client: &reqwest::Client,
+    url: &str,
+    proxy_source: Option<&str>,
+    part_path: &Path,
+    os_name: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let offset = fs::metadata(part_path).map(|m| m.len()).unwrap_or(0);
+    let mut request = client.get(url);
+    if offset > 0 {
+        println!("Resuming download from byte {}", offset);
+        request = request.header(reqwest::header::RANGE, format!("bytes={}-", offset));
+    }
+
+    let response = send_request(request, url, proxy_source).await?;
+    if response.url().as_str() != url {
+        println!("Redirected to {}", response.url());
+    }
+    let status = response.status();
+    if status == reqwest::StatusCode::RANGE_NOT_SATISFIABLE {
+        // The partial file already holds the whole body; verification decides if it's usable
+        return Ok(());
+    }
+    if !status.is_success() {
+        let message = format!("Download of {} failed: {}", url, status);
+        if status.is_server_error() {
+            return Err(TransientError(message).into());
+        }
+        return Err(message.into());
+    }
+
+    // Servers that ignore the Range header send the full body, so start over
+    let resumed = offset > 0 && status == reqwest::StatusCode::PARTIAL_CONTENT;
+    if status != reqwest::StatusCode::OK && !resumed {
+        return Err(format!("Download of {} returned unexpected status {}", url, status).into());
+    }
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("unknown")
+        .to_string();
+    let expected_size = response
+        .content_length()
+        .map(|len| if resumed { offset + len } else { len });
+
+    let mut file = if resumed {
+        OpenOptions::new().append(true).open(part_path)?
+    } else {
+        File::create(part_path)?
+    };
+
+    // cargo swallows carriage returns, so report plain byte counts periodically
+    let mut downloaded = if resumed { offset } else { 0 };
+    let mut next_report = downloaded + PROGRESS_INTERVAL_BYTES;
+    let progress_file = progress_file_path();
+    let mut next_progress_write = downloaded;
+    let mut stream = response.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|e| TransientError(e.to_string()))?;
+        file.write_all(&chunk)?;
+
+        downloaded += chunk.len() as u64;
+        if downloaded >= next_report {
+            match expected_size {
+                Some(total) => println!(
+                    "cargo:warning=Ollama download: {}/{} MB",
+                    downloaded / BYTES_PER_MB,
+                    total / BYTES_PER_MB
+                ),
+                None => println!("cargo:warning=Ollama download: {} MB", downloaded / BYTES_PER_MB),
+            }
+            next_report = downloaded + PROGRESS_INTERVAL_BYTES;
+        }
+        if let Some(path) = progress_file.as_deref().filter(|_| downloaded >= next_progress_write) {
+            // Progress reporting must never fail the download itself
+            let _ = write_progress_file(path, downloaded, expected_size);
+            next_progress_write = downloaded + PROGRESS_FILE_INTERVAL_BYTES;
+        }
+    }
+    if let Some(path) = &progress_file {
+        let _ = write_progress_file(path, downloaded, expected_size);
+    }
+    file.flush()?;
+    drop(file);
+
+    if !has_executable_magic(part_path, os_name)? {
+        fs::remove_file(part_path)?;
+        return Err(format!(
+            "Download of {} is not a {} executable (content-type: {})",
+            url, os_name, content_type
+        )
+        .into());
+    }
+
+    let actual_size = fs::metadata(part_path)?.len();
+    match expected_size {
+        Some(expected) if expected != actual_size => Err(TransientError(format!(
+            "Incomplete download: got {} of {} bytes",
+            actual_size, expected
+        ))
+        .into()),
+        _ => Ok(()),
+    }
+}
+
+/// With `OLLAMA_DOWNLOAD_PROGRESS_FILE=1`, where download progress is kept as
+/// JSON for CI wrappers to poll.
+fn progress_file_path() -> Option<PathBuf> {
+    if !is_env_flag_set("OLLAMA_DOWNLOAD_PROGRESS_FILE") {
+        return None;
+    }
+    env::var_os("OUT_DIR").map(|dir| PathBuf::from(dir).join("download-progress.json"))
+}
+
+/// Replaces the progress file in one rename, so pollers never see it half-written.
+fn write_progress_file(path: &Path, downloaded: u64, total: Option<u64>) -> io::Result<()> {
+    let percent = match total {
+        Some(total) if total > 0 => format!("{:.1}", downloaded as f64 * 100.0 / total as f64),
+        _ => "null".to_string(),
+    };
+    let total = total.map_or("null".to_string(), |total| total.to_string());
+    let json = format!(
+        "{{\"total\": {}, \"downloaded\": {}, \"percent\": {}}}\n",
+        total, downloaded, percent
+    );
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, json)?;
+    fs::rename(&tmp_path, path)
+}
+
+/// Makes the binary executable on Unix-like systems
+fn make_executable(path: &Path) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mut perms = fs::metadata(path)?.permissions();
+        perms.set_mode(0o755);
+        fs::set_permissions(path, perms)?;
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+    Ok(())
+}
+
+fn is_env_flag_set(name: &str) -> bool {
+    env::var(name).is_ok_and(|value| matches!(value.trim(), "1" | "true" | "yes"))
+}
+
+/// Copies `source` onto `target_path` via a temp file next to it and a rename,
+/// so an interrupted build never leaves a truncated binary under the final name.
+fn install_local_binary(source: &Path, target_path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    println!("cargo:rerun-if-changed={}", source.display());
+    let mut tmp_name = target_path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp_path = target_path.with_file_name(tmp_name);
+    fs::copy(source, &tmp_path)
+        .map_err(|e| format!("Failed to copy {}: {}", source.display(), e))?;
+    make_executable(&tmp_path)?;
+    fs::rename(&tmp_path, target_path)?;
+    Ok(())
+}
+
+fn read_meta(binary_dir: &Path) -> serde_json::Map<String, serde_json::Value> {
+    fs::read_to_string(binary_dir.join(META_FILE))
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+/// Records (or with `None`, forgets) what is known about one binary.
+fn write_meta(
+    binary_dir: &Path,
+    filename: &str,
+    entry: Option<serde_json::Value>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut meta = read_meta(binary_dir);
+    match entry {
+        Some(entry) => meta.insert(filename.to_string(), entry),
+        None => meta.remove(filename),
+    };
+    fs::write(binary_dir.join(META_FILE), serde_json::to_string_pretty(&meta)?)?;
+    Ok(())
+}
+
+/// Finds the release tag `latest` points at from the first redirect of `url`,
+/// e.g. `.../releases/download/v0.5.7/ollama-linux-amd64`. Mirrors that don't
+/// redirect leave the version unknown.
+async fn resolve_release_tag(url: &str) -> Option<String> {
+    let (client, proxy_source) = build_download_client(reqwest::redirect::Policy::none()).ok()?;
+    let response = send_request(client.head(url), url, proxy_source).await.ok()?;
+    let location = response.headers().get(reqwest::header::LOCATION)?.to_str().ok()?;
+    let location = reqwest::Url::parse(location).ok()?;
+    let mut segments = location.path_segments()?;
+    segments.find(|segment| *segment == "download")?;
+    segments.next().map(|tag| tag.to_string())
+}
+
+/// Installs the Ollama binary for the build host and returns its release tag,
+/// if known. With `OLLAMA_UNIVERSAL_MACOS` set, macOS builds also fetch the
+/// other architecture's binary so a universal app can run either natively.
+fn download_ollama() -> Result<Option<String>, Box<dyn std::error::Error>> {
+    let (os, arch) = (env::consts::OS, env::consts::ARCH);
+    let universal = os == "macos" && is_env_flag_set("OLLAMA_UNIVERSAL_MACOS");
+    let other_arch = if arch == "aarch64" { "x86_64" } else { "aarch64" };
+
+    if is_env_flag_set("OLLAMA_DOWNLOAD_DRY_RUN") {
+        print_download_plan(get_platform_info(os, arch)?)?;
+        if universal {
+            print_download_plan(get_platform_info(os, other_arch)?)?;
+        }
+        return Ok(None);
+    }
+
+    let version = install_ollama(get_platform_info(os, arch)?, true)?;
+    if universal {
+        println!("Also bundling Ollama for macOS {} (universal build)", other_arch);
+        install_ollama(get_platform_info(os, other_arch)?, false)?;
+    }
+    Ok(version)
+}
+
+/// `OLLAMA_DOWNLOAD_DRY_RUN`: reports where a platform's binary would be
+/// downloaded from and to, without touching the network or the filesystem.
+fn print_download_plan(
+    (os_name, arch_name, filename, download_filename): (String, String, String, String),
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (release_url, version) = get_release_url()?;
+    let target_path = PathBuf::from(env::var("CARGO_MANIFEST_DIR")?)
+        .join("binaries")
+        .join(&filename);
+    println!(
+        "cargo:warning=Dry run: would download Ollama {} for {}-{} from {}/{} to {}",
+        version,
+        os_name,
+        arch_name,
+        release_url,
+        download_filename,
+        target_path.display()
+    );
+    Ok(())
+}
+
+/// Installs one platform's Ollama binary and returns its release tag, if
+/// known. `OLLAMA_BINARY_PATH` names a single binary, so it's only used for
+/// the host platform.
+fn install_ollama(
+    (os_name, arch_name, filename, download_filename): (String, String, String, String),
+    is_host: bool,
+) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    let binary_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR")?).join("binaries");
+    fs::create_dir_all(&binary_dir)?;
+
+    let target_path = binary_dir.join(&filename);
+
+    // A locally provided binary always wins over downloading
+    if let Some(local_path) = env::var_os("OLLAMA_BINARY_PATH").filter(|_| is_host) {
+        let local_path = PathBuf::from(local_path);
+        install_local_binary(&local_path, &target_path)
+            .map_err(|e| format!("Invalid OLLAMA_BINARY_PATH: {}", e))?;
+        write_meta(&binary_dir, &filename, None)?;
+        println!("Using local Ollama binary from {}", local_path.display());
+        return Ok(None);
+    }
+
+    // Then a copy vendored in-tree, for network-free builds
+    let vendored_dir = env::var_os("OLLAMA_VENDORED_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| binary_dir.join("vendored"));
+    let vendored_path = vendored_dir.join(&filename);
+    if vendored_path.is_file() {
+        install_local_binary(&vendored_path, &target_path)?;
+        write_meta(&binary_dir, &filename, None)?;
+        println!("Using vendored Ollama binary from {}", vendored_path.display());
+        return Ok(None);
+    }
+
+    // Later builds reuse the binary, so its release comes from the metadata
+    let recorded = read_meta(&binary_dir).remove(&filename);
+    let recorded_version = || {
+        recorded
+            .as_ref()
+            .and_then(|entry| entry.get("version")?.as_str())
+            .map(str::to_string)
+    };
+
+    // A cached binary that no longer matches its recorded checksum is
+    // corrupt, so fetch it again
+    let recorded_sha256 = recorded
+        .as_ref()
+        .and_then(|entry| entry.get("sha256")?.as_str());
+    if let (true, Some(expected)) = (target_path.exists(), recorded_sha256) {
+        let actual = sha256_file(&target_path)?;
+        if actual != expected {
+            println!(
+                "cargo:warning=Cached {} doesn't match its recorded checksum, downloading it again",
+                filename
+            );
+            fs::remove_file(&target_path)?;
+        }
+    }
+    if is_env_flag_set("OLLAMA_SKIP_DOWNLOAD") {
+        println!("cargo:warning=OLLAMA_SKIP_DOWNLOAD is set, skipping the Ollama download");
+        return Ok(recorded_version());
+    }
+
     let rt = tokio::runtime::Runtime::new()?;
     rt.block_on(async {
-        let (os_name, arch_name, filename, download_filename) = get_platform_info()?;
-        
-        let binary_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR")?).join("binaries");
-        fs::create_dir_all(&binary_dir)?;
-
-        let target_path = binary_dir.join(&filename);
-        
         // Only proceed if the exact platform-specific binary doesn't exist
         if !target_path.exists() {
-            println!("Downloading Ollama for {}-{}...", os_name, arch_name);
+            let (release_url, version) = get_release_url()?;
+            let mut resolved_version = (version != "latest").then(|| version.clone());
+            println!("Downloading Ollama {} for {}-{}...", version, os_name, arch_name);
+            println!("cargo:warning=Bundling Ollama version: {}", version);
             
-            let url = format!(
-                "https://github.com/ollama/ollama/releases/latest/download/{}",
-                download_filename
-            );
+            let url = format!("{}/{}", release_url, download_filename);
             println!("Downloading from URL: {}", url);
 
-            let response = reqwest::get(&url).await.map_err(|e| e.to_string())?;
-            let bytes = response.bytes().await.map_err(|e| e.to_string())?;
+            // Release assets redirect to a CDN; cap the chain so a redirect
+            // loop fails instead of hanging the build
+            let (client, proxy_source) =
+                build_download_client(reqwest::redirect::Policy::limited(MAX_REDIRECTS))?;
+            if let Some(source) = proxy_source {
+                println!("Using proxy from {}", source);
+            }
+            if version == "latest" {
+                resolved_version = resolve_release_tag(&url).await;
+            }
+
+            let expected = with_retries("Checksum fetch", || {
+                fetch_expected_checksum(&client, proxy_source, &release_url, &download_filename)
+            })
+            .await?;
 
-            let mut file = File::create(&target_path).map_err(|e| e.to_string())?;
-            file.write_all(&bytes).map_err(|e| e.to_string())?;
+            let part_path = binary_dir.join(format!("{}.part", filename));
+            // Retries resume from whatever the previous attempt left in the .part file
+            with_retries("Download", || {
+                download_to_part(&client, &url, proxy_source, &part_path, &os_name)
+            })
+            .await?;
 
-            // Make the binary executable on Unix-like systems
-            #[cfg(unix)]
-            {
-                use std::os::unix::fs::PermissionsExt;
-                let mut perms = fs::metadata(&target_path)?.permissions();
-                perms.set_mode(0o755);
-                fs::set_permissions(&target_path, perms)?;
+            // Verify the binary before it takes the final name
+            let actual = sha256_file(&part_path)?;
+            if actual != expected {
+                fs::remove_file(&part_path)?;
+                return Err(ChecksumMismatch {
+                    filename: download_filename,
+                    expected,
+                    actual,
+                }
+                .into());
             }
+            // Only a complete, verified, executable file ever takes the final name
+            make_executable(&part_path)?;
+            fs::rename(&part_path, &target_path)?;
+            let downloaded_at = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default();
+            write_meta(
+                &binary_dir,
+                &filename,
+                Some(serde_json::json!({
+                    "source_url": url,
+                    "version": resolved_version,
+                    "sha256": actual,
+                    "downloaded_at": downloaded_at,
+                    "size": fs::metadata(&target_path)?.len(),
+                })),
+            )?;
 
             println!("Successfully downloaded Ollama for {}-{}", os_name, arch_name);
+            Ok(resolved_version)
         } else {
             println!("Platform-specific Ollama binary already exists for {}-{}", os_name, arch_name);
+            Ok(recorded_version())
         }
-
-        Ok(())
     })
 }
 
 fn main() {
-    // Download Ollama if needed
-    if let Err(e) = download_ollama() {
-        println!("cargo:warning=Failed to download Ollama: {}", e);
+    println!("cargo:rerun-if-env-changed=OLLAMA_VERSION");
+    println!("cargo:rerun-if-env-changed=OLLAMA_DOWNLOAD_BASE_URL");
+    println!("cargo:rerun-if-env-changed=OLLAMA_SKIP_DOWNLOAD");
+    println!("cargo:rerun-if-env-changed=OLLAMA_BINARY_PATH");
+    println!("cargo:rerun-if-env-changed=OLLAMA_VENDORED_DIR");
+    println!("cargo:rerun-if-env-changed=OLLAMA_UNIVERSAL_MACOS");
+    println!("cargo:rerun-if-env-changed=OLLAMA_DOWNLOAD_PROGRESS_FILE");
+    println!("cargo:rerun-if-env-changed=OLLAMA_DOWNLOAD_DRY_RUN");
+    for name in PROXY_ENV_VARS.iter().chain(&["NO_PROXY", "no_proxy"]) {
+        println!("cargo:rerun-if-env-changed={}", name);
     }
 
+    // Download Ollama if needed
+    let version = match download_ollama() {
+        Ok(version) => version,
+        Err(e) => {
+            // A corrupted binary must never be bundled, so fail the build outright
+            if e.is::<ChecksumMismatch>() {
+                panic!("Refusing to bundle Ollama: {}", e);
+            }
+            println!("cargo:warning=Failed to download Ollama: {}", e);
+            None
+        }
+    };
+    // Empty when unknown, e.g. for a locally provided binary
+    println!(
+        "cargo:rustc-env=BUNDLED_OLLAMA_VERSION={}",
+        version.unwrap_or_default()
+    );
+
     tauri_build::build()
 } 
\ No newline at end of file
@@ -0,0 +1,79 @@
+//! Launching the app at login, registered with the OS login-items
+//! mechanism (a launch agent on macOS, the registry on Windows, an XDG
+//! autostart entry on Linux). Login launches start hidden in the tray.
+
+use crate::error::CommandError;
+use crate::AppState;
+use tauri::AppHandle;
+use tracing::{info, warn};
+
+fn auto_launch(app: &AppHandle) -> Result<auto_launch::AutoLaunch, String> {
+    if !auto_launch::AutoLaunch::is_support() {
+        return Err("Launching at login isn't supported on this platform".to_string());
+    }
+    let exe = std::env::current_exe()
+        .map_err(|e| format!("Could not locate the app executable: {}", e))?;
+    let name = app
+        .config()
+        .package
+        .product_name
+        .clone()
+        .unwrap_or_else(|| "Open WebUI".to_string());
+    auto_launch::AutoLaunchBuilder::new()
+        .set_app_name(&name)
+        .set_app_path(&exe.to_string_lossy())
+        // Login items would need the .app bundle; an agent works with the binary
+        .set_use_launch_agent(true)
+        .set_args(&["--hidden"])
+        .build()
+        .map_err(|e| format!("Could not set up launching at login: {}", e))
+}
+
+/// Brings `autostart` in the config in line with the OS, which has the final
+/// say: the login item may have been removed outside the app.
+pub fn sync_config(app: &AppHandle, state: &AppState) {
+    let enabled = match auto_launch(app)
+        .and_then(|launcher| launcher.is_enabled().map_err(|e| e.to_string()))
+    {
+        Ok(enabled) => enabled,
+        Err(e) => {
+            warn!("Could not check the login item: {}", e);
+            return;
+        }
+    };
+    let mut config = state.config.lock().unwrap();
+    if config.autostart != enabled {
+        config.autostart = enabled;
+        if let Err(e) = config.save(&state.config_path) {
+            warn!("Failed to save config: {}", e);
+        }
+    }
+}
+
+/// Whether the app is currently registered to launch at login.
+#[tauri::command]
+pub fn get_autostart(app: AppHandle) -> Result<bool, CommandError> {
+    auto_launch(&app)?
+        .is_enabled()
+        .map_err(|e| CommandError::Other(format!("Could not check the login item: {}", e)))
+}
+
+#[tauri::command]
+pub fn set_autostart(
+    app: AppHandle,
+    state: tauri::State<'_, AppState>,
+    enabled: bool,
+) -> Result<(), CommandError> {
+    let launcher = auto_launch(&app)?;
+    let result = if enabled {
+        launcher.enable()
+    } else {
+        launcher.disable()
+    };
+    result.map_err(|e| CommandError::Other(format!("Could not update the login item: {}", e)))?;
+    info!("Launch at login {}", if enabled { "enabled" } else { "disabled" });
+
+    let mut config = state.config.lock().unwrap();
+    config.autostart = enabled;
+    config.save(&state.config_path).map_err(CommandError::Config)
+}
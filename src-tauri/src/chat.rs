@@ -0,0 +1,139 @@
+//! Proxies chat completions through the Rust side for a UI that only talks
+//! Tauri events.
+
+use crate::error::CommandError;
+use crate::tasks::{self, InFlight};
+use crate::{backend_base_url, AppState};
+use tauri::{Manager, Window};
+
+/// In-flight chats by request id, so they can be cancelled
+pub struct Chats(InFlight);
+
+impl Default for Chats {
+    fn default() -> Self {
+        Chats(InFlight::new("chat"))
+    }
+}
+
+#[derive(Clone, serde::Deserialize)]
+pub struct ChatRequest {
+    /// Chosen by the caller; tags every `chat-chunk` and names the chat for
+    /// `cancel_chat`
+    id: String,
+    /// The user's backend session token
+    token: String,
+    model: String,
+    messages: Vec<serde_json::Value>,
+}
+
+#[derive(Clone, serde::Serialize)]
+struct ChatChunk {
+    id: String,
+    content: String,
+    done: bool,
+}
+
+/// Streams a completion from the backend's chat endpoint, emitting each
+/// piece of the reply to `window` as a `chat-chunk` event and a final one
+/// with `done` set. Resolves once the reply is complete, fails or is
+/// cancelled with `cancel_chat`.
+#[tauri::command]
+pub async fn chat_stream(
+    window: Window,
+    chats: tauri::State<'_, Chats>,
+    request: ChatRequest,
+) -> Result<(), CommandError> {
+    let id = request.id.clone();
+    chats.0.run(&id, stream_chat(window, request)).await
+}
+
+/// Aborts an in-flight `chat_stream`, dropping its request to the backend.
+#[tauri::command]
+pub async fn cancel_chat(chats: tauri::State<'_, Chats>, id: String) -> Result<(), CommandError> {
+    chats.0.cancel(&id).await
+}
+
+async fn stream_chat(window: Window, request: ChatRequest) -> Result<(), CommandError> {
+    let state = window
+        .try_state::<AppState>()
+        .ok_or_else(|| CommandError::Other("App state is not available".to_string()))?;
+    let url = format!("{}/api/chat/completions", backend_base_url(&state.backend_url()));
+    let response = state
+        .streaming_client
+        .post(&url)
+        .bearer_auth(&request.token)
+        .json(&serde_json::json!({
+            "model": request.model,
+            "messages": request.messages,
+            "stream": true,
+        }))
+        .send()
+        .await
+        .map_err(|e| {
+            CommandError::ServiceUnavailable(format!(
+                "The backend is not reachable at {}: {}",
+                url, e
+            ))
+        })?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(CommandError::Other(format!(
+            "The backend failed to answer: {} {}",
+            status, body
+        )));
+    }
+
+    tasks::for_each_line(response, |line| Ok(emit_chat_line(&window, &request.id, line)?))
+        .await?;
+    finish(&window, &request.id);
+    Ok(())
+}
+
+/// Handles one line of the reply, either an SSE `data:` line with an
+/// OpenAI-style delta or an Ollama NDJSON line. Returns whether the reply
+/// is complete.
+fn emit_chat_line(window: &Window, id: &str, line: &[u8]) -> Result<bool, String> {
+    let line = String::from_utf8_lossy(line);
+    let line = line.trim();
+    let data = line.strip_prefix("data:").map(str::trim_start).unwrap_or(line);
+    if data.is_empty() || line.starts_with(':') {
+        return Ok(false);
+    }
+    if data == "[DONE]" {
+        return Ok(true);
+    }
+
+    let value: serde_json::Value = serde_json::from_str(data)
+        .map_err(|e| format!("Unexpected chat response from the backend: {}", e))?;
+    if let Some(error) = value.get("error") {
+        return Err(format!("The backend failed to answer: {}", error));
+    }
+    let content = value
+        .pointer("/choices/0/delta/content")
+        .or_else(|| value.pointer("/message/content"))
+        .and_then(|content| content.as_str())
+        .unwrap_or_default();
+    if !content.is_empty() {
+        let _ = window.emit(
+            "chat-chunk",
+            ChatChunk {
+                id: id.to_string(),
+                content: content.to_string(),
+                done: false,
+            },
+        );
+    }
+    Ok(value.get("done").and_then(|done| done.as_bool()).unwrap_or(false))
+}
+
+fn finish(window: &Window, id: &str) {
+    let _ = window.emit(
+        "chat-chunk",
+        ChatChunk {
+            id: id.to_string(),
+            content: String::new(),
+            done: true,
+        },
+    );
+}
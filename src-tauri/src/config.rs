@@ -0,0 +1,306 @@
+//! User settings persisted as `config.json` in the app config directory.
+//!
+//! Environment variables (`OLLAMA_PORT`, `BACKEND_BASE_URL`, ...) still take
+//! precedence over these values when the app starts.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const CONFIG_FILE: &str = "config.json";
+
+pub const DEFAULT_OLLAMA_PORT: u16 = 11434;
+pub const DEFAULT_BACKEND_URL: &str = "http://localhost:8080";
+pub const DEFAULT_POLL_ATTEMPTS: u64 = 30;
+pub const DEFAULT_POLL_INTERVAL_MS: u64 = 1000;
+pub const DEFAULT_LOG_LEVEL: &str = "info";
+/// Bounds for `default_num_ctx`; anything outside is certainly a mistake
+pub const MIN_NUM_CTX: u32 = 512;
+pub const MAX_NUM_CTX: u32 = 1 << 20;
+pub const MIN_ZOOM: f64 = 0.5;
+pub const MAX_ZOOM: f64 = 3.0;
+/// Ollama environment variables users may set through `ollama_flags`. Only
+/// tuning knobs; anything that changes where Ollama listens or stores data
+/// is managed by the app itself.
+pub const OLLAMA_FLAGS: &[&str] = &[
+    "OLLAMA_FLASH_ATTENTION",
+    "OLLAMA_KV_CACHE_TYPE",
+    "OLLAMA_NUM_PARALLEL",
+    "OLLAMA_MAX_LOADED_MODELS",
+    "OLLAMA_MAX_QUEUE",
+    "OLLAMA_GPU_OVERHEAD",
+    "OLLAMA_SCHED_SPREAD",
+    "OLLAMA_LOAD_TIMEOUT",
+    "OLLAMA_DEBUG",
+];
+/// Levels the backend accepts in `GLOBAL_LOG_LEVEL`
+pub const BACKEND_LOG_LEVELS: &[&str] = &["CRITICAL", "ERROR", "WARNING", "INFO", "DEBUG"];
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Port the bundled Ollama listens on
+    pub ollama_port: u16,
+    /// Ollama URL passed to the backend instead of the local instance, e.g.
+    /// to use Ollama on another host
+    pub ollama_base_url: Option<String>,
+    /// Base URL the backend is served from
+    pub backend_url: String,
+    /// Show a system tray icon; without one, closing the window quits
+    pub use_tray: bool,
+    /// Hide the window instead of quitting when it is closed. Unset follows
+    /// the platform convention: hide on macOS, quit elsewhere.
+    pub close_to_tray: Option<bool>,
+    /// How Ollama is polled while it starts
+    pub ollama_poll: PollConfig,
+    /// How the backend is polled while it starts
+    pub backend_poll: PollConfig,
+    /// Boot into the tray without showing the window; also `--hidden`
+    pub start_minimized: bool,
+    /// Whether the app is registered to launch (hidden) at login. Synced
+    /// from the OS at startup and only changed through `set_autostart`.
+    pub autostart: bool,
+    /// `tracing` filter directives for the app's own logs
+    pub log_level: String,
+    /// The backend's log level, passed as `GLOBAL_LOG_LEVEL`; `None` keeps
+    /// its default of INFO
+    pub backend_log_level: Option<String>,
+    /// Directory Ollama stores models in, passed as `OLLAMA_MODELS`
+    pub models_dir: Option<String>,
+    /// Model loaded into memory at startup to cut first-prompt latency
+    pub preload_model: Option<String>,
+    /// How long Ollama keeps an idle model in memory, passed as
+    /// `OLLAMA_KEEP_ALIVE`: a duration like "30m", seconds, or "-1" to never
+    /// unload. Longer avoids slow reloads at the cost of holding RAM/VRAM.
+    /// `None` keeps Ollama's default of 5 minutes.
+    pub model_keep_alive: Option<String>,
+    /// Performance flags set as environment variables on Ollama, limited to
+    /// `OLLAMA_FLAGS`
+    pub ollama_flags: HashMap<String, String>,
+    /// Context length sent as `num_ctx` with requests; `None` uses each
+    /// model's own default
+    pub default_num_ctx: Option<u32>,
+    /// Where the backend keeps its database and uploads, passed as
+    /// `DATA_DIR`; defaults to `backend` under the app data directory
+    pub backend_data_dir: Option<String>,
+    /// Virtualenv directory the backend's Python runs from; also `PYTHON_VENV`
+    pub python_venv: Option<String>,
+    /// Model the user last selected, reselected on the next launch
+    pub last_model: Option<String>,
+    /// Page zoom applied to every window, between `MIN_ZOOM` and `MAX_ZOOM`
+    pub zoom_factor: f64,
+    /// Whether the app spawns Ollama itself
+    pub ollama_auto_start: bool,
+    /// Cleared once the user finishes onboarding
+    pub first_run: bool,
+    /// Attach to an Ollama and backend run by hand instead of spawning them;
+    /// also enabled by `DEV_MODE=1`
+    pub dev_mode: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            ollama_port: DEFAULT_OLLAMA_PORT,
+            ollama_base_url: None,
+            backend_url: DEFAULT_BACKEND_URL.to_string(),
+            use_tray: true,
+            close_to_tray: None,
+            ollama_poll: PollConfig::default(),
+            backend_poll: PollConfig::default(),
+            start_minimized: false,
+            autostart: false,
+            log_level: DEFAULT_LOG_LEVEL.to_string(),
+            backend_log_level: None,
+            models_dir: None,
+            preload_model: None,
+            last_model: None,
+            zoom_factor: 1.0,
+            python_venv: None,
+            backend_data_dir: None,
+            default_num_ctx: None,
+            ollama_flags: HashMap::new(),
+            model_keep_alive: None,
+            ollama_auto_start: true,
+            first_run: true,
+            dev_mode: false,
+        }
+    }
+}
+
+/// Startup polling for one service. The total wait is
+/// `max_attempts × interval_ms`.
+#[derive(Clone, Copy, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct PollConfig {
+    pub max_attempts: u64,
+    pub interval_ms: u64,
+}
+
+impl Default for PollConfig {
+    fn default() -> Self {
+        PollConfig {
+            max_attempts: DEFAULT_POLL_ATTEMPTS,
+            interval_ms: DEFAULT_POLL_INTERVAL_MS,
+        }
+    }
+}
+
+impl PollConfig {
+    pub fn interval(&self) -> Duration {
+        Duration::from_millis(self.interval_ms)
+    }
+
+    pub fn total(&self) -> Duration {
+        // Both come from the user, so saturate rather than overflow
+        Duration::from_millis(self.interval_ms.saturating_mul(self.max_attempts))
+    }
+
+    /// Keeps the interval but picks enough attempts to wait about `secs`.
+    pub fn with_total_secs(self, secs: u64) -> Self {
+        PollConfig {
+            max_attempts: (secs.saturating_mul(1000) / self.interval_ms.max(1)).max(1),
+            ..self
+        }
+    }
+}
+
+impl Config {
+    pub fn close_to_tray(&self) -> bool {
+        self.close_to_tray.unwrap_or(cfg!(target_os = "macos"))
+    }
+
+    pub fn path(config_dir: &Path) -> PathBuf {
+        config_dir.join(CONFIG_FILE)
+    }
+
+    /// Loads the config, using defaults if the file doesn't exist yet. Fields
+    /// absent from the file take their default values.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        match fs::read_to_string(path) {
+            Ok(json) => serde_json::from_str(&json)
+                .map_err(|e| format!("Invalid config in {}: {}", path.display(), e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Config::default()),
+            Err(e) => Err(format!("Failed to read {}: {}", path.display(), e)),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(path, json).map_err(|e| e.to_string())
+    }
+
+    /// Rejects values the app couldn't start with.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.ollama_port == 0 {
+            return Err("ollama_port must be between 1 and 65535".to_string());
+        }
+        if let Some(url) = &self.ollama_base_url {
+            crate::parse_ollama_url(url)?;
+        }
+        if crate::parse_backend_url(&self.backend_url).is_none() {
+            return Err(format!(
+                "backend_url '{}' must be an http(s) URL with a host",
+                self.backend_url
+            ));
+        }
+        let polls = [("ollama_poll", self.ollama_poll), ("backend_poll", self.backend_poll)];
+        for (name, poll) in polls {
+            if poll.max_attempts == 0 || poll.interval_ms == 0 {
+                return Err(format!("{} needs at least one attempt and a non-zero interval", name));
+            }
+        }
+        for key in self.ollama_flags.keys() {
+            validate_ollama_flag(key)?;
+        }
+        if let Some(num_ctx) = self.default_num_ctx {
+            validate_num_ctx(num_ctx)?;
+        }
+        if let Some(keep_alive) = &self.model_keep_alive {
+            validate_keep_alive(keep_alive)?;
+        }
+        if !(MIN_ZOOM..=MAX_ZOOM).contains(&self.zoom_factor) {
+            return Err(format!(
+                "zoom_factor must be between {} and {}, got {}",
+                MIN_ZOOM, MAX_ZOOM, self.zoom_factor
+            ));
+        }
+        if let Some(level) = &self.backend_log_level {
+            validate_backend_log_level(level)?;
+        }
+        tracing_subscriber::EnvFilter::try_new(&self.log_level)
+            .map_err(|e| format!("log_level '{}' is invalid: {}", self.log_level, e))?;
+        Ok(())
+    }
+}
+
+pub fn validate_ollama_flag(key: &str) -> Result<(), String> {
+    if !OLLAMA_FLAGS.contains(&key) {
+        return Err(format!(
+            "{} is not a supported Ollama flag, expected one of {}",
+            key,
+            OLLAMA_FLAGS.join(", ")
+        ));
+    }
+    Ok(())
+}
+
+pub fn validate_backend_log_level(level: &str) -> Result<(), String> {
+    if !BACKEND_LOG_LEVELS.contains(&level) {
+        return Err(format!(
+            "{} is not a backend log level, expected one of {}",
+            level,
+            BACKEND_LOG_LEVELS.join(", ")
+        ));
+    }
+    Ok(())
+}
+
+pub fn validate_num_ctx(num_ctx: u32) -> Result<(), String> {
+    if !(MIN_NUM_CTX..=MAX_NUM_CTX).contains(&num_ctx) {
+        return Err(format!(
+            "num_ctx must be between {} and {}, got {}",
+            MIN_NUM_CTX, MAX_NUM_CTX, num_ctx
+        ));
+    }
+    Ok(())
+}
+
+/// Accepts what Ollama does for keep-alive: a whole number of seconds or a Go
+/// duration such as "1h30m", either optionally negative for "forever".
+pub fn validate_keep_alive(value: &str) -> Result<(), String> {
+    let invalid = || {
+        format!(
+            "keep-alive '{}' must be a number of seconds or a duration like 30m, 1h or -1",
+            value
+        )
+    };
+    let duration = value.trim().strip_prefix('-').unwrap_or(value.trim());
+    if duration.is_empty() {
+        return Err(invalid());
+    }
+    if duration.parse::<u64>().is_ok() {
+        return Ok(());
+    }
+
+    let mut rest = duration;
+    while !rest.is_empty() {
+        let number_len = rest
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .ok_or_else(invalid)?;
+        if rest[..number_len].parse::<f64>().is_err() {
+            return Err(invalid());
+        }
+        rest = &rest[number_len..];
+        let unit_len = rest.find(|c: char| c.is_ascii_digit()).unwrap_or(rest.len());
+        if !matches!(&rest[..unit_len], "ns" | "us" | "µs" | "ms" | "s" | "m" | "h") {
+            return Err(invalid());
+        }
+        rest = &rest[unit_len..];
+    }
+    Ok(())
+}
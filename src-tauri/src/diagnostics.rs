@@ -0,0 +1,228 @@
+//! Commands that help users and support figure out why something is broken.
+
+use crate::error::CommandError;
+use crate::{backend_health_url, bundled_ollama_version, models, AppState};
+use std::error::Error as _;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::AppHandle;
+
+#[derive(Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub enum ConnectionStatus {
+    Ok,
+    ConnectionRefused,
+    Timeout,
+    DnsError,
+    Http4xx,
+    Http5xx,
+    /// Any other failure; `detail` has the underlying error
+    Other,
+}
+
+#[derive(serde::Serialize)]
+pub struct DiagnoseResult {
+    url: String,
+    status: ConnectionStatus,
+    http_status: Option<u16>,
+    detail: Option<String>,
+}
+
+/// Probes `target` ("ollama" or "backend") and classifies what went wrong,
+/// so the UI can say more than "unreachable".
+#[tauri::command]
+pub async fn diagnose_connection(
+    state: tauri::State<'_, AppState>,
+    target: String,
+) -> Result<DiagnoseResult, CommandError> {
+    let url = match target.as_str() {
+        "ollama" => format!("{}/api/version", state.backend_ollama_url),
+        "backend" => backend_health_url(&state.backend_url()),
+        _ => {
+            return Err(CommandError::InvalidInput(format!(
+                "Unknown target '{}', expected ollama or backend",
+                target
+            )))
+        }
+    };
+
+    let (status, http_status, detail) = match state.http_client.get(&url).send().await {
+        Ok(response) => {
+            let code = response.status();
+            let status = if code.is_server_error() {
+                ConnectionStatus::Http5xx
+            } else if code.is_client_error() {
+                ConnectionStatus::Http4xx
+            } else {
+                ConnectionStatus::Ok
+            };
+            (status, Some(code.as_u16()), None)
+        }
+        Err(e) => (classify_error(&e), None, Some(e.to_string())),
+    };
+    Ok(DiagnoseResult {
+        url,
+        status,
+        http_status,
+        detail,
+    })
+}
+
+#[derive(serde::Serialize)]
+pub struct GpuInfo {
+    /// "metal", "cuda", "cpu" or "unknown"
+    backend: &'static str,
+    available: bool,
+}
+
+/// Reports whether inference runs on a GPU, so the UI can warn about a
+/// CPU-only fallback. A loaded model's `size_vram` is what Ollama actually
+/// does; with nothing loaded this falls back to what the platform offers.
+#[tauri::command]
+pub async fn get_gpu_info(state: tauri::State<'_, AppState>) -> Result<GpuInfo, CommandError> {
+    let platform = platform_gpu().await;
+    let loaded = models::running_models(&state).await.unwrap_or_default();
+    if loaded.is_empty() {
+        return Ok(GpuInfo {
+            backend: platform.unwrap_or("unknown"),
+            available: platform.is_some(),
+        });
+    }
+
+    if loaded.iter().any(|model| model.size_vram > 0) {
+        Ok(GpuInfo {
+            backend: platform.unwrap_or("unknown"),
+            available: true,
+        })
+    } else {
+        Ok(GpuInfo {
+            backend: "cpu",
+            available: false,
+        })
+    }
+}
+
+/// The GPU backend Ollama can use on this machine, if any. Only Apple
+/// Silicon and NVIDIA GPUs are recognized.
+async fn platform_gpu() -> Option<&'static str> {
+    if cfg!(all(target_os = "macos", target_arch = "aarch64")) {
+        return Some("metal");
+    }
+    let nvidia = tokio::task::spawn_blocking(|| which::which("nvidia-smi").is_ok())
+        .await
+        .unwrap_or(false);
+    nvidia.then_some("cuda")
+}
+
+/// Bundles the logs, the config (credentials redacted), platform info,
+/// versions, service statuses and PIDs into a zip for bug reports. Writes to
+/// `path` if given, otherwise to the temp directory, and returns where.
+#[tauri::command]
+pub async fn export_diagnostics(
+    app: AppHandle,
+    state: tauri::State<'_, AppState>,
+    path: Option<String>,
+) -> Result<String, CommandError> {
+    let mut config = serde_json::to_value(&*state.config.lock().unwrap())?;
+    for key in ["ollama_base_url", "backend_url"] {
+        if let Some(value) = config.get_mut(key) {
+            redact_url(value);
+        }
+    }
+
+    let system = serde_json::json!({
+        "os": std::env::consts::OS,
+        "arch": std::env::consts::ARCH,
+        "app_version": app.package_info().version.to_string(),
+        "bundled_ollama_version": bundled_ollama_version(),
+        "dev_mode": state.dev_mode,
+        "status": crate::get_system_status(state.clone()).await.ok(),
+        "pids": crate::get_service_pids(state.clone()).await.ok(),
+    });
+
+    let path = match path {
+        Some(path) => PathBuf::from(path),
+        None => {
+            let timestamp = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default();
+            std::env::temp_dir().join(format!("open-webui-diagnostics-{}.zip", timestamp))
+        }
+    };
+    let log_dir = state.log_dir.clone();
+    let zip_path = path.clone();
+    tokio::task::spawn_blocking(move || write_diagnostics(&zip_path, &log_dir, &config, &system))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| CommandError::Io(format!("Failed to write {}: {}", path.display(), e)))?;
+    Ok(path.display().to_string())
+}
+
+/// Masks the password of a URL-valued config entry.
+fn redact_url(value: &mut serde_json::Value) {
+    let Some(mut url) = value.as_str().and_then(|url| reqwest::Url::parse(url).ok()) else {
+        return;
+    };
+    if url.password().is_some() && url.set_password(Some("REDACTED")).is_ok() {
+        *value = url.to_string().into();
+    }
+}
+
+fn write_diagnostics(
+    path: &Path,
+    log_dir: &Path,
+    config: &serde_json::Value,
+    system: &serde_json::Value,
+) -> io::Result<()> {
+    let mut zip = zip::ZipWriter::new(File::create(path)?);
+    let options = zip::write::FileOptions::default();
+
+    zip.start_file("config.json", options)?;
+    zip.write_all(&serde_json::to_vec_pretty(config)?)?;
+    zip.start_file("system.json", options)?;
+    zip.write_all(&serde_json::to_vec_pretty(system)?)?;
+
+    // Rotation keeps only the recent logs around, so take all of them
+    if let Ok(entries) = fs::read_dir(log_dir) {
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if !name.ends_with(".log") || !entry.path().is_file() {
+                continue;
+            }
+            zip.start_file(format!("logs/{}", name), options)?;
+            io::copy(&mut File::open(entry.path())?, &mut zip)?;
+        }
+    }
+
+    zip.finish()?;
+    Ok(())
+}
+
+fn classify_error(error: &reqwest::Error) -> ConnectionStatus {
+    if error.is_timeout() {
+        return ConnectionStatus::Timeout;
+    }
+    if !error.is_connect() {
+        return ConnectionStatus::Other;
+    }
+
+    // reqwest doesn't expose the cause directly, so walk the source chain
+    let mut source = error.source();
+    while let Some(cause) = source {
+        if let Some(io) = cause.downcast_ref::<std::io::Error>() {
+            match io.kind() {
+                std::io::ErrorKind::ConnectionRefused => return ConnectionStatus::ConnectionRefused,
+                std::io::ErrorKind::TimedOut => return ConnectionStatus::Timeout,
+                _ => {}
+            }
+        }
+        // hyper reports resolver failures as plain "dns error" messages
+        if cause.to_string().contains("dns error") {
+            return ConnectionStatus::DnsError;
+        }
+        source = cause.source();
+    }
+    ConnectionStatus::Other
+}
@@ -0,0 +1,100 @@
+//! The error every command returns. It serializes as `{ kind, message }` so
+//! the frontend can branch on `kind` and still show `message` as is.
+
+use std::fmt;
+
+#[derive(Clone, Debug)]
+pub enum CommandError {
+    /// Ollama or the backend couldn't be reached
+    ServiceUnavailable(String),
+    Timeout(String),
+    NotFound(String),
+    /// The action clashes with one already in progress or the current mode
+    Conflict(String),
+    InvalidInput(String),
+    Io(String),
+    Config(String),
+    Other(String),
+}
+
+impl CommandError {
+    fn kind(&self) -> &'static str {
+        match self {
+            CommandError::ServiceUnavailable(_) => "service_unavailable",
+            CommandError::Timeout(_) => "timeout",
+            CommandError::NotFound(_) => "not_found",
+            CommandError::Conflict(_) => "conflict",
+            CommandError::InvalidInput(_) => "invalid_input",
+            CommandError::Io(_) => "io",
+            CommandError::Config(_) => "config",
+            CommandError::Other(_) => "other",
+        }
+    }
+
+    fn message(&self) -> &str {
+        match self {
+            CommandError::ServiceUnavailable(message)
+            | CommandError::Timeout(message)
+            | CommandError::NotFound(message)
+            | CommandError::Conflict(message)
+            | CommandError::InvalidInput(message)
+            | CommandError::Io(message)
+            | CommandError::Config(message)
+            | CommandError::Other(message) => message,
+        }
+    }
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl std::error::Error for CommandError {}
+
+impl serde::Serialize for CommandError {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+        let mut error = serializer.serialize_struct("CommandError", 2)?;
+        error.serialize_field("kind", self.kind())?;
+        error.serialize_field("message", self.message())?;
+        error.end()
+    }
+}
+
+/// Internal helpers still report plain messages; without more to go on
+/// those are `Other`
+impl From<String> for CommandError {
+    fn from(message: String) -> Self {
+        CommandError::Other(message)
+    }
+}
+
+impl From<std::io::Error> for CommandError {
+    fn from(error: std::io::Error) -> Self {
+        match error.kind() {
+            std::io::ErrorKind::NotFound => CommandError::NotFound(error.to_string()),
+            std::io::ErrorKind::TimedOut => CommandError::Timeout(error.to_string()),
+            _ => CommandError::Io(error.to_string()),
+        }
+    }
+}
+
+impl From<reqwest::Error> for CommandError {
+    fn from(error: reqwest::Error) -> Self {
+        if error.is_timeout() {
+            CommandError::Timeout(error.to_string())
+        } else if error.is_connect() {
+            CommandError::ServiceUnavailable(error.to_string())
+        } else {
+            CommandError::Other(error.to_string())
+        }
+    }
+}
+
+impl From<serde_json::Error> for CommandError {
+    fn from(error: serde_json::Error) -> Self {
+        CommandError::Other(error.to_string())
+    }
+}
@@ -0,0 +1,325 @@
+//! Exercises the Ollama and backend lifecycle against a stub that stands in
+//! for both binaries, so no real Ollama or Python backend is needed.
+
+use super::*;
+use std::os::unix::fs::PermissionsExt;
+
+/// Serves the endpoints the app polls. Run with `serve` it acts as Ollama on
+/// `OLLAMA_HOST`, otherwise as the backend on `HOST`/`PORT`. Like the
+/// backend's frontend mount, any other path gets an HTML page with a 200, so
+/// a probe of the wrong route can't pass. `GET /crash` makes it exit with an
+/// error.
+const STUB: &str = r#"import http.server, json, os, sys
+
+if "serve" in sys.argv:
+    host, port = os.environ["OLLAMA_HOST"].rsplit(":", 1)
+else:
+    host, port = os.environ["HOST"], os.environ["PORT"]
+
+class Handler(http.server.BaseHTTPRequestHandler):
+    def do_GET(self):
+        if self.path == "/crash":
+            os._exit(1)
+        bodies = {"/api/version": {"version": "0.0.0"}, "/health": {"status": True}}
+        body = bodies.get(self.path)
+        self.send_response(200)
+        if body:
+            self.send_header("Content-Type", "application/json")
+            self.end_headers()
+            self.wfile.write(json.dumps(body).encode())
+        else:
+            self.send_header("Content-Type", "text/html")
+            self.end_headers()
+            self.wfile.write(b"<!doctype html><html><body></body></html>")
+
+    def log_message(self, *args):
+        pass
+
+http.server.HTTPServer((host, int(port)), Handler).serve_forever()
+"#;
+
+/// Writes the stub once for all tests. The shebang names Python directly
+/// rather than going through `env`, so the process keeps the stub's name as
+/// PID records expect.
+fn stub_path() -> PathBuf {
+    static STUB_PATH: std::sync::OnceLock<PathBuf> = std::sync::OnceLock::new();
+    STUB_PATH
+        .get_or_init(|| {
+            let python = which::which("python3").expect("the stub needs python3");
+            let dir = tempfile::tempdir().unwrap().keep();
+            let path = dir.join("stub");
+            fs::write(&path, format!("#!{}\n{}", python.display(), STUB)).unwrap();
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+            path
+        })
+        .clone()
+}
+
+fn free_port() -> u16 {
+    std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port()
+}
+
+fn test_state(dir: &Path) -> AppState {
+    let ollama_port = free_port();
+    let backend_url = format!("http://127.0.0.1:{}", free_port());
+    let poll = PollConfig {
+        max_attempts: 50,
+        interval_ms: 100,
+    };
+    AppState {
+        config: std::sync::Mutex::new(Config::default()),
+        config_path: dir.join("config.json"),
+        instance_lock: dir.join("instance.lock"),
+        ollama_process: Arc::new(Mutex::new(None)),
+        backend_process: Arc::new(Mutex::new(None)),
+        pid_file: pids::PidFile::new(dir),
+        ollama_port,
+        ollama_binary: Some(stub_path()),
+        ollama_options: OllamaOptions {
+            args: Vec::new(),
+            env: Vec::new(),
+        },
+        backend_ollama_url: ollama_base_url(ollama_port),
+        backend_url: std::sync::Mutex::new(reqwest::Url::parse(&backend_url).unwrap()),
+        backend_command: BackendCommand {
+            program: stub_path().to_string_lossy().into_owned(),
+            args: Vec::new(),
+            dir: dir.to_path_buf(),
+            venv: None,
+        },
+        backend_data_dir: dir.join("backend"),
+        log_dir: dir.join("logs"),
+        http_client: build_http_client(
+            reqwest::Client::builder()
+                .timeout(STATUS_TIMEOUT)
+                .connect_timeout(STATUS_CONNECT_TIMEOUT),
+        ),
+        streaming_client: reqwest::Client::new(),
+        backend_max_restarts: DEFAULT_BACKEND_MAX_RESTARTS,
+        ollama_hang_threshold: 0,
+        ollama_poll: poll,
+        backend_poll: poll,
+        frontend_ready: tokio::sync::Notify::new(),
+        starting: AtomicBool::new(true),
+        shutting_down: AtomicBool::new(false),
+        window_shown: AtomicBool::new(false),
+        use_tray: false,
+        dev_mode: false,
+        start_hidden: false,
+        system: std::sync::Mutex::new(sysinfo::System::new()),
+    }
+}
+
+async fn wait_for_ollama(state: &AppState) -> bool {
+    wait_for(|| async { ping_ollama(state).await.is_ok() }, state.ollama_poll, |_| {}).await
+}
+
+async fn wait_for_backend(state: &AppState) -> bool {
+    wait_for(
+        || async { backend_readiness(state).await == BackendReadiness::Ready },
+        state.backend_poll,
+        |_| {},
+    )
+    .await
+}
+
+#[tokio::test]
+async fn startup_polling_waits_for_both_services() {
+    let dir = tempfile::tempdir().unwrap();
+    let state = test_state(dir.path());
+
+    let mut ticks = 0;
+    let startup = bring_up_ollama(&state, |_| ticks += 1).await.unwrap();
+    assert_eq!(startup, OllamaStartup::Healthy);
+    assert!(ticks >= 1);
+    assert!(state.ollama_process.lock().await.is_some());
+
+    assert!(claim_backend_port(&state).await.unwrap());
+    let backend = start_backend(&state).await.unwrap();
+    *state.backend_process.lock().await = Some(backend);
+    assert!(wait_for_backend(&state).await);
+    assert!(state.backend_data_dir.is_dir());
+
+    stop_services(&state).await;
+}
+
+#[tokio::test]
+async fn polling_gives_up_when_nothing_answers() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut state = test_state(dir.path());
+    state.ollama_poll = PollConfig {
+        max_attempts: 3,
+        interval_ms: 10,
+    };
+
+    let mut attempts = 0;
+    let healthy = wait_for(
+        || async { ping_ollama(&state).await.is_ok() },
+        state.ollama_poll,
+        |attempt| attempts = attempt,
+    )
+    .await;
+    assert!(!healthy);
+    assert_eq!(attempts, 3);
+}
+
+#[tokio::test]
+async fn running_ollama_is_reused() {
+    let dir = tempfile::tempdir().unwrap();
+    let state = test_state(dir.path());
+    // Started by someone else, so it never goes in the slot
+    let other = start_ollama(&state).await.unwrap();
+    assert!(wait_for_ollama(&state).await);
+
+    let startup = bring_up_ollama(&state, |_| {}).await.unwrap();
+    assert_eq!(startup, OllamaStartup::Reused);
+    assert!(state.ollama_process.lock().await.is_none());
+
+    kill_process(other).await;
+}
+
+#[tokio::test]
+async fn external_ollama_is_never_spawned() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut state = test_state(dir.path());
+    state.backend_ollama_url = format!("http://127.0.0.1:{}", free_port());
+    state.ollama_poll = PollConfig {
+        max_attempts: 3,
+        interval_ms: 10,
+    };
+
+    let startup = bring_up_ollama(&state, |_| {}).await.unwrap();
+    assert_eq!(startup, OllamaStartup::Unavailable);
+    assert!(state.ollama_process.lock().await.is_none());
+}
+
+#[tokio::test]
+async fn running_backend_is_reused() {
+    let dir = tempfile::tempdir().unwrap();
+    let state = test_state(dir.path());
+    let backend = start_backend(&state).await.unwrap();
+    *state.backend_process.lock().await = Some(backend);
+    assert!(wait_for_backend(&state).await);
+
+    // A second launch finds ours on the port and leaves it be
+    let url = state.backend_url();
+    assert!(!claim_backend_port(&state).await.unwrap());
+    assert_eq!(state.backend_url(), url);
+
+    stop_services(&state).await;
+}
+
+#[tokio::test]
+async fn backend_moves_off_a_port_held_by_another_program() {
+    let dir = tempfile::tempdir().unwrap();
+    let state = test_state(dir.path());
+    let url = state.backend_url();
+    let _other = std::net::TcpListener::bind(("127.0.0.1", url.port().unwrap())).unwrap();
+
+    assert!(claim_backend_port(&state).await.unwrap());
+    assert_ne!(state.backend_url().port(), url.port());
+}
+
+/// Runs the real supervisor against a backend that crashes, until the
+/// supervisor reports `event`
+async fn supervise_crash(state: &AppState, event: &'static str) -> Vec<(&'static str, u32)> {
+    let backend = start_backend(state).await.unwrap();
+    *state.backend_process.lock().await = Some(backend);
+    assert!(wait_for_backend(state).await);
+
+    let events = std::sync::Mutex::new(Vec::new());
+    let supervisor = run_backend_supervisor(state, |event, attempts| {
+        events.lock().unwrap().push((event, attempts));
+    });
+    let scenario = async {
+        let crash = format!("{}/crash", backend_base_url(&state.backend_url()));
+        let _ = state.http_client.get(crash).send().await;
+        let reported = wait_for(
+            || async { events.lock().unwrap().iter().any(|(name, _)| *name == event) },
+            PollConfig {
+                max_attempts: 150,
+                interval_ms: 100,
+            },
+            |_| {},
+        )
+        .await;
+        assert!(reported, "the supervisor never reported {}", event);
+    };
+    tokio::join!(supervisor, async {
+        scenario.await;
+        // Also ends the supervisor
+        stop_services(state).await;
+    });
+    events.into_inner().unwrap()
+}
+
+#[tokio::test]
+async fn crashed_backend_is_restarted_by_the_supervisor() {
+    let dir = tempfile::tempdir().unwrap();
+    let state = test_state(dir.path());
+
+    let events = supervise_crash(&state, "backend-restarted").await;
+    assert_eq!(events, [("backend-restarted", 1)]);
+}
+
+#[tokio::test]
+async fn supervisor_gives_up_after_the_restart_budget() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut state = test_state(dir.path());
+    state.backend_max_restarts = 0;
+
+    let events = supervise_crash(&state, "backend-failed").await;
+    assert_eq!(events, [("backend-failed", 0)]);
+    assert!(state.backend_process.lock().await.is_none());
+}
+
+#[tokio::test]
+async fn shutdown_stops_services_and_clears_pids() {
+    let dir = tempfile::tempdir().unwrap();
+    let state = test_state(dir.path());
+    let ollama = start_ollama(&state).await.unwrap();
+    *state.ollama_process.lock().await = Some(ollama);
+    let backend = start_backend(&state).await.unwrap();
+    *state.backend_process.lock().await = Some(backend);
+    assert!(wait_for_ollama(&state).await);
+    assert!(wait_for_backend(&state).await);
+    assert!(dir.path().join("pids.json").is_file());
+
+    stop_services(&state).await;
+    assert!(state.shutting_down.load(Ordering::SeqCst));
+    assert!(state.ollama_process.lock().await.is_none());
+    assert!(state.backend_process.lock().await.is_none());
+    assert!(!dir.path().join("pids.json").exists());
+    assert!(ping_ollama(&state).await.is_err());
+    assert!(backend_readiness(&state).await == BackendReadiness::Unreachable);
+
+    // Shutting down twice is a no-op
+    stop_services(&state).await;
+}
+
+#[tokio::test]
+async fn orphans_of_a_crashed_session_are_reaped() {
+    let dir = tempfile::tempdir().unwrap();
+    let state = test_state(dir.path());
+    let ollama = start_ollama(&state).await.unwrap();
+    assert!(wait_for_ollama(&state).await);
+
+    // The app died without stopping Ollama; the next launch cleans up
+    pids::PidFile::new(dir.path()).reap_stale();
+    let mut ollama = ollama;
+    let exited = wait_for(
+        || {
+            let exited = matches!(ollama.try_wait(), Ok(Some(_)));
+            async move { exited }
+        },
+        state.ollama_poll,
+        |_| {},
+    )
+    .await;
+    assert!(exited);
+    assert!(!dir.path().join("pids.json").exists());
+}
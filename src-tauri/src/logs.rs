@@ -0,0 +1,153 @@
+//! In-app log viewer: reads the tail of the app's own log file and follows
+//! it as it grows.
+
+use crate::error::CommandError;
+use crate::AppState;
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tokio::sync::Mutex;
+use tokio::task::AbortHandle;
+use tracing::warn;
+
+/// File name prefix of the daily-rotated app log, e.g. `open-webui.2024-05-01.log`
+pub const APP_LOG_PREFIX: &str = "open-webui";
+/// How much of the file is read per step when seeking back from the end
+const TAIL_CHUNK: u64 = 8 * 1024;
+const FOLLOW_INTERVAL: Duration = Duration::from_millis(500);
+
+/// The log follower started by `subscribe_logs`, if any
+#[derive(Default)]
+pub struct LogFollower(Mutex<Option<AbortHandle>>);
+
+/// The most recently modified app log file in `log_dir`.
+fn current_log_file(log_dir: &Path) -> Option<PathBuf> {
+    let prefix = format!("{}.", APP_LOG_PREFIX);
+    fs::read_dir(log_dir)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            entry
+                .file_name()
+                .to_str()
+                .is_some_and(|name| name.starts_with(&prefix) && name.ends_with(".log"))
+        })
+        .max_by_key(|entry| entry.metadata().and_then(|m| m.modified()).ok())
+        .map(|entry| entry.path())
+}
+
+/// Reads the last `count` lines of `path` without loading the whole file.
+fn tail_lines(path: &Path, count: usize) -> io::Result<Vec<String>> {
+    let mut file = File::open(path)?;
+    let mut pos = file.metadata()?.len();
+    let mut buffer = Vec::new();
+
+    // One newline more than requested guarantees the first kept line is whole
+    while pos > 0 && buffer.iter().filter(|&&b| b == b'\n').count() <= count {
+        let read = TAIL_CHUNK.min(pos);
+        pos -= read;
+        file.seek(SeekFrom::Start(pos))?;
+        let mut chunk = vec![0; read as usize];
+        file.read_exact(&mut chunk)?;
+        chunk.append(&mut buffer);
+        buffer = chunk;
+    }
+
+    let text = String::from_utf8_lossy(&buffer);
+    let lines: Vec<&str> = text.lines().collect();
+    let start = lines.len().saturating_sub(count);
+    Ok(lines[start..].iter().map(|line| line.to_string()).collect())
+}
+
+/// Returns the last `lines` lines of the current app log.
+#[tauri::command]
+pub fn read_recent_logs(
+    state: tauri::State<'_, AppState>,
+    lines: usize,
+) -> Result<Vec<String>, CommandError> {
+    let Some(path) = current_log_file(&state.log_dir) else {
+        return Ok(Vec::new());
+    };
+    tail_lines(&path, lines)
+        .map_err(|e| CommandError::Io(format!("Failed to read {}: {}", path.display(), e)))
+}
+
+/// Emits every line appended to the app log from now on as a `log-line`
+/// event. Calling it again while already subscribed is a no-op.
+#[tauri::command]
+pub async fn subscribe_logs(
+    app: AppHandle,
+    follower: tauri::State<'_, LogFollower>,
+) -> Result<(), CommandError> {
+    let mut follower = follower.0.lock().await;
+    if follower.as_ref().is_some_and(|handle| !handle.is_finished()) {
+        return Ok(());
+    }
+    let log_dir = app
+        .try_state::<AppState>()
+        .ok_or_else(|| CommandError::Other("App state is not available".to_string()))?
+        .log_dir
+        .clone();
+    *follower = Some(tokio::spawn(follow(app, log_dir)).abort_handle());
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn unsubscribe_logs(follower: tauri::State<'_, LogFollower>) -> Result<(), CommandError> {
+    if let Some(handle) = follower.0.lock().await.take() {
+        handle.abort();
+    }
+    Ok(())
+}
+
+async fn follow(app: AppHandle, log_dir: PathBuf) {
+    // Start at the end of the current file; only new lines are interesting
+    let mut position = current_log_file(&log_dir).and_then(|path| {
+        let len = fs::metadata(&path).ok()?.len();
+        Some((path, len))
+    });
+
+    loop {
+        tokio::time::sleep(FOLLOW_INTERVAL).await;
+        let Some(path) = current_log_file(&log_dir) else {
+            continue;
+        };
+        // A new day's file (or a truncated one) is read from the start
+        let offset = match &position {
+            Some((current, offset)) if *current == path => *offset,
+            _ => 0,
+        };
+        match read_new_lines(&path, offset) {
+            Ok((lines, offset)) => {
+                for line in lines {
+                    let _ = app.emit_all("log-line", line);
+                }
+                position = Some((path, offset));
+            }
+            Err(e) => warn!("Failed to follow {}: {}", path.display(), e),
+        }
+    }
+}
+
+/// Reads the complete lines written after `offset`, returning them with the
+/// offset to continue from. A trailing partial line is left for next time.
+fn read_new_lines(path: &Path, offset: u64) -> io::Result<(Vec<String>, u64)> {
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+    let offset = if len < offset { 0 } else { offset };
+
+    file.seek(SeekFrom::Start(offset))?;
+    let mut buffer = Vec::new();
+    file.take(len - offset).read_to_end(&mut buffer)?;
+    let Some(end) = buffer.iter().rposition(|&b| b == b'\n') else {
+        return Ok((Vec::new(), offset));
+    };
+
+    let lines = String::from_utf8_lossy(&buffer[..end])
+        .lines()
+        .map(|line| line.to_string())
+        .collect();
+    Ok((lines, offset + end as u64 + 1))
+}
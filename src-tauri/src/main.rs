@@ -3,163 +3,2453 @@
     windows_subsystem = "windows"
 )]
 
-use std::process::Command;
+mod autostart;
+mod chat;
+mod config;
+mod diagnostics;
+mod error;
+#[cfg(all(test, unix))]
+mod lifecycle_tests;
+mod logs;
+mod models;
+mod pids;
+mod single_instance;
+mod tasks;
+mod updates;
+
+use std::fs::{self, File};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tauri::{
-    CustomMenuItem, Manager, SystemTray, SystemTrayEvent, SystemTrayMenu,
+    AppHandle, CustomMenuItem, Manager, SystemTray, SystemTrayEvent, SystemTrayMenu,
     SystemTrayMenuItem,
 };
 use tokio::sync::Mutex;
+use config::{Config, PollConfig};
+use error::CommandError;
+use tracing::{error, info, warn};
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
+
+/// Number of log files kept per service, including the current one
+const MAX_LOG_FILES: usize = 5;
+const WINDOW_STATE_FILE: &str = "window-state.json";
+const MAIN_WINDOW: &str = "main";
+const SETTINGS_WINDOW: &str = "settings";
+const DEFAULT_BACKEND_MAX_RESTARTS: u32 = 5;
+/// Consecutive failed health checks of a running Ollama before it is
+/// considered hung and restarted
+const DEFAULT_OLLAMA_HANG_THRESHOLD: u32 = 10;
+/// How long to wait for the frontend to report readiness before showing the
+/// window anyway
+const FRONTEND_READY_TIMEOUT: Duration = Duration::from_secs(10);
+const GRACEFUL_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+const STATUS_TIMEOUT: Duration = Duration::from_secs(2);
+const STATUS_CONNECT_TIMEOUT: Duration = Duration::from_secs(1);
+const HEALTH_POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// Consecutive polls a service must disagree with its last known state
+/// before a `service-up`/`service-down` event is emitted
+const HEALTH_DEBOUNCE_POLLS: u32 = 2;
+const SUPERVISOR_INTERVAL: Duration = Duration::from_secs(2);
+const MAX_RESTART_BACKOFF: Duration = Duration::from_secs(30);
+/// Release tag of the Ollama bundled at build time; empty if unknown, e.g.
+/// when the build used a locally provided binary
+const BUNDLED_OLLAMA_VERSION: &str = env!("BUNDLED_OLLAMA_VERSION");
+/// How many ports above the configured one to try when it is taken
+const BACKEND_PORT_SEARCH_RANGE: u16 = 20;
+
+/// How to launch the Python backend
+struct BackendCommand {
+    program: String,
+    args: Vec<String>,
+    dir: PathBuf,
+    /// Virtualenv the backend runs in, if any
+    venv: Option<PathBuf>,
+}
+
+impl BackendCommand {
+    /// Reads `BACKEND_CMD` (program and args, shell-quoted) and `BACKEND_DIR`,
+    /// defaulting to `python3 -m backend.app` run from the parent directory.
+    /// `PYTHON_VENV` (or the configured `python_venv`) swaps `python3` for
+    /// that virtualenv's interpreter.
+    fn from_env(python_venv: Option<&str>) -> Self {
+        let command = std::env::var("BACKEND_CMD")
+            .ok()
+            .and_then(|value| match shell_words::split(&value) {
+                Ok(words) if !words.is_empty() => Some(words),
+                Ok(_) => None,
+                Err(e) => {
+                    warn!("Invalid BACKEND_CMD '{}': {}, using the default", value, e);
+                    None
+                }
+            });
+        let command_is_default = command.is_none();
+        let (program, args) = match command {
+            Some(mut words) => {
+                let program = words.remove(0);
+                (program, words)
+            }
+            None => (
+                "python3".to_string(),
+                vec!["-m".to_string(), "backend.app".to_string()],
+            ),
+        };
+        let venv = std::env::var("PYTHON_VENV")
+            .ok()
+            .or_else(|| python_venv.map(str::to_string))
+            .filter(|venv| !venv.trim().is_empty())
+            .map(PathBuf::from);
+        // An explicit BACKEND_CMD names its own interpreter
+        let program = match &venv {
+            Some(venv) if command_is_default => venv_python(venv).display().to_string(),
+            _ => program,
+        };
+
+        // Move up one directory to find the backend module by default
+        let dir = std::env::var_os("BACKEND_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("../"));
+
+        BackendCommand {
+            program,
+            args,
+            dir,
+            venv,
+        }
+    }
+}
+
+/// The Python interpreter inside a virtualenv.
+fn venv_python(venv: &Path) -> PathBuf {
+    if cfg!(windows) {
+        venv.join("Scripts").join("python.exe")
+    } else {
+        venv.join("bin").join("python")
+    }
+}
+
+/// Extra options for the spawned `ollama serve`
+struct OllamaOptions {
+    args: Vec<String>,
+    env: Vec<(String, String)>,
+}
+
+impl OllamaOptions {
+    /// Reads `OLLAMA_EXTRA_ARGS` (shell-quoted flags appended after `serve`)
+    /// and `OLLAMA_ENV_<NAME>` variables, each set as `<NAME>` on the child,
+    /// e.g. `OLLAMA_ENV_OLLAMA_NUM_PARALLEL=4`. `OLLAMA_HOST` is always
+    /// overridden so the app can find the server.
+    fn from_env() -> Self {
+        let args = match std::env::var("OLLAMA_EXTRA_ARGS") {
+            Ok(value) => shell_words::split(&value).unwrap_or_else(|e| {
+                warn!("Invalid OLLAMA_EXTRA_ARGS '{}': {}, ignoring it", value, e);
+                Vec::new()
+            }),
+            Err(_) => Vec::new(),
+        };
+        let env = std::env::vars()
+            .filter_map(|(name, value)| {
+                let name = name.strip_prefix("OLLAMA_ENV_")?;
+                (!name.is_empty()).then(|| (name.to_string(), value))
+            })
+            .collect();
+        OllamaOptions { args, env }
+    }
+}
 
 struct AppState {
+    /// Persisted user settings; only ever held briefly, so a std mutex lets
+    /// synchronous event handlers read it too
+    config: std::sync::Mutex<Config>,
+    config_path: PathBuf,
+    instance_lock: PathBuf,
     ollama_process: Arc<Mutex<Option<std::process::Child>>>,
     backend_process: Arc<Mutex<Option<std::process::Child>>>,
+    pid_file: pids::PidFile,
+    ollama_port: u16,
+    /// Spawned instead of the bundled Ollama, so tests can run a stub
+    #[cfg(test)]
+    ollama_binary: Option<PathBuf>,
+    ollama_options: OllamaOptions,
+    /// The Ollama the app and the backend talk to, normally the local
+    /// instance; see `external_ollama`
+    backend_ollama_url: String,
+    /// Base URL of the backend; its port moves if the configured one is taken
+    backend_url: std::sync::Mutex<reqwest::Url>,
+    backend_command: BackendCommand,
+    /// Passed to the backend as `DATA_DIR`, where it keeps its database
+    backend_data_dir: PathBuf,
+    log_dir: PathBuf,
+    /// Shared client for status checks; its timeouts keep a stalled socket
+    /// from blocking the polling loops
+    http_client: reqwest::Client,
+    /// Client for long-running requests such as model pulls, where only the
+    /// connection phase is bounded
+    streaming_client: reqwest::Client,
+    backend_max_restarts: u32,
+    /// See `DEFAULT_OLLAMA_HANG_THRESHOLD`; 0 disables the hang watchdog
+    ollama_hang_threshold: u32,
+    /// How each service is polled while it starts (or restarts)
+    ollama_poll: PollConfig,
+    backend_poll: PollConfig,
+    /// Signalled by the frontend once its root component has mounted
+    frontend_ready: tokio::sync::Notify,
+    /// True until the initial startup sequence has finished
+    starting: AtomicBool,
+    /// Set once services are deliberately stopped so supervisors don't revive them
+    shutting_down: AtomicBool,
+    /// Whether the app has a tray to hide into; without one, closing the
+    /// window quits and the window is never started hidden
+    use_tray: bool,
+    /// Set once the main window has been revealed, so startup never shows it
+    /// a second time and steals focus
+    window_shown: AtomicBool,
+    /// Attach to externally run Ollama and backend instead of spawning them
+    dev_mode: bool,
+    /// Stay in the tray after startup, e.g. when launched at login
+    start_hidden: bool,
+    /// Kept between `get_resource_usage` calls, since CPU usage is measured
+    /// over the interval since the previous refresh
+    system: std::sync::Mutex<sysinfo::System>,
+}
+
+impl AppState {
+    fn backend_url(&self) -> reqwest::Url {
+        self.backend_url.lock().unwrap().clone()
+    }
+
+    /// Whether `OLLAMA_BASE_URL` or `ollama_base_url` points somewhere other
+    /// than the local instance, which the app then never spawns
+    fn external_ollama(&self) -> bool {
+        self.backend_ollama_url != ollama_base_url(self.ollama_port)
+    }
+}
+
+/// Parses the environment variable `name`, falling back to `default` when it
+/// is unset or invalid.
+fn env_or<T>(name: &str, default: T) -> T
+where
+    T: std::str::FromStr + std::fmt::Display,
+{
+    match std::env::var(name) {
+        Ok(value) => value.trim().parse().unwrap_or_else(|_| {
+            warn!("Invalid {} '{}', falling back to {}", name, value, default);
+            default
+        }),
+        Err(_) => default,
+    }
+}
+
+fn is_env_flag_set(name: &str) -> bool {
+    std::env::var(name).is_ok_and(|value| matches!(value.trim(), "1" | "true" | "yes"))
+}
+
+/// Parses an http(s) URL with a host and port, as required for the backend.
+fn parse_backend_url(value: &str) -> Option<reqwest::Url> {
+    reqwest::Url::parse(value.trim()).ok().filter(|url| {
+        matches!(url.scheme(), "http" | "https")
+            && url.host_str().is_some()
+            && url.port_or_known_default().is_some()
+    })
+}
+
+/// Reads `BACKEND_BASE_URL`, falling back to the configured URL (and then to
+/// `DEFAULT_BACKEND_URL`) when it is unset or invalid.
+fn backend_url_from_env(configured: &str) -> reqwest::Url {
+    let fallback = || {
+        parse_backend_url(configured).unwrap_or_else(|| {
+            warn!(
+                "Invalid backend_url '{}' in config, falling back to {}",
+                configured,
+                config::DEFAULT_BACKEND_URL
+            );
+            reqwest::Url::parse(config::DEFAULT_BACKEND_URL).expect("default backend URL is valid")
+        })
+    };
+    let Ok(value) = std::env::var("BACKEND_BASE_URL") else {
+        return fallback();
+    };
+    parse_backend_url(&value).unwrap_or_else(|| {
+        let url = fallback();
+        warn!("Invalid BACKEND_BASE_URL '{}', falling back to {}", value, url);
+        url
+    })
+}
+
+/// Formats `url` without the trailing slash `Url` adds to bare origins.
+fn backend_base_url(url: &reqwest::Url) -> String {
+    url.as_str().trim_end_matches('/').to_string()
+}
+
+/// The backend's own health route. `/api/health` is not one: it falls
+/// through to the frontend, which answers any path with index.html.
+fn backend_health_url(url: &reqwest::Url) -> String {
+    format!("{}/health", backend_base_url(url))
+}
+
+fn ollama_base_url(port: u16) -> String {
+    format!("http://localhost:{}", port)
+}
+
+/// Checks an Ollama URL override, e.g. one pointing at another host.
+fn parse_ollama_url(value: &str) -> Result<reqwest::Url, String> {
+    let url = reqwest::Url::parse(value.trim())
+        .map_err(|e| format!("Ollama URL '{}' is not a valid URL: {}", value, e))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(format!("Ollama URL '{}' must use http or https", value));
+    }
+    if url.host_str().is_none() {
+        return Err(format!("Ollama URL '{}' has no host", value));
+    }
+    Ok(url)
+}
+
+/// The Ollama URL handed to the backend: `OLLAMA_BASE_URL`, then the
+/// configured override, then the local instance on `port`.
+fn backend_ollama_url(configured: Option<&str>, port: u16) -> String {
+    let env = std::env::var("OLLAMA_BASE_URL").ok();
+    let Some(value) = env.as_deref().or(configured) else {
+        return ollama_base_url(port);
+    };
+    match parse_ollama_url(value) {
+        Ok(url) => backend_base_url(&url),
+        Err(e) => {
+            error!("{}, using the local Ollama instead", e);
+            ollama_base_url(port)
+        }
+    }
 }
 
 #[tauri::command]
-async fn check_ollama_status() -> Result<bool, String> {
-    let response = reqwest::get("http://localhost:11434/api/version")
-        .await
-        .map_err(|e| e.to_string())?;
+async fn check_ollama_status(state: tauri::State<'_, AppState>) -> Result<bool, CommandError> {
+    let url = format!("{}/api/version", state.backend_ollama_url);
+    let response = state.http_client.get(url).send().await?;
     Ok(response.status().is_success())
 }
 
 #[tauri::command]
-async fn check_backend_status() -> Result<bool, String> {
-    let response = reqwest::get("http://localhost:8080/api/health")
-        .await
-        .map_err(|e| e.to_string())?;
+async fn check_backend_status(state: tauri::State<'_, AppState>) -> Result<bool, CommandError> {
+    let response = state
+        .http_client
+        .get(backend_health_url(&state.backend_url()))
+        .send()
+        .await?;
     Ok(response.status().is_success())
 }
 
-fn get_ollama_path() -> String {
+/// Times a GET of `url`, failing if it doesn't answer with a success status.
+async fn ping(client: &reqwest::Client, url: String) -> Result<Duration, String> {
+    let started = std::time::Instant::now();
+    let response = client.get(url).send().await.map_err(|e| e.to_string())?;
+    let elapsed = started.elapsed();
+    if !response.status().is_success() {
+        return Err(format!("Responded with {}", response.status()));
+    }
+    Ok(elapsed)
+}
+
+async fn ping_ollama(state: &AppState) -> Result<Duration, String> {
+    let url = format!("{}/api/version", state.backend_ollama_url);
+    ping(&state.http_client, url).await
+}
+
+async fn ping_backend(state: &AppState) -> Result<Duration, String> {
+    let url = backend_health_url(&state.backend_url());
+    ping(&state.http_client, url).await
+}
+
+/// Round-trip times in milliseconds; `None` if the service didn't respond
+#[derive(serde::Serialize)]
+struct ServiceLatencies {
+    ollama_ms: Option<u64>,
+    backend_ms: Option<u64>,
+}
+
+#[tauri::command]
+async fn ping_services(
+    state: tauri::State<'_, AppState>,
+) -> Result<ServiceLatencies, CommandError> {
+    let (ollama, backend) = tokio::join!(ping_ollama(&state), ping_backend(&state));
+    let millis = |result: Result<Duration, String>| result.ok().map(|d| d.as_millis() as u64);
+    Ok(ServiceLatencies {
+        ollama_ms: millis(ollama),
+        backend_ms: millis(backend),
+    })
+}
+
+#[derive(serde::Serialize)]
+struct SystemStatus {
+    ollama_up: bool,
+    ollama_health: ServiceHealth,
+    ollama_version: Option<String>,
+    backend_up: bool,
+    backend_health: ServiceHealth,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+enum ServiceHealth {
+    Starting,
+    Up,
+    Down,
+    Unknown,
+}
+
+/// Combines an HTTP check with the state of the child process, so a service
+/// that is still warming up isn't reported as crashed.
+async fn service_health(
+    process: &Mutex<Option<std::process::Child>>,
+    http_up: bool,
+    starting: bool,
+) -> ServiceHealth {
+    if http_up {
+        return ServiceHealth::Up;
+    }
+    match process.lock().await.as_mut().map(|child| child.try_wait()) {
+        Some(Ok(None)) => ServiceHealth::Starting,
+        Some(Ok(Some(_))) => ServiceHealth::Down,
+        Some(Err(_)) => ServiceHealth::Unknown,
+        // Nothing spawned yet, or it was stopped or is run by someone else
+        None if starting => ServiceHealth::Starting,
+        None => ServiceHealth::Down,
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct OllamaVersion {
+    version: String,
+}
+
+/// Returns the running Ollama's version, or `None` if it can't be reached.
+/// The outer `Option` says whether Ollama answered at all, the inner one
+/// whether it reported a version we could parse.
+async fn fetch_ollama_version(state: &AppState) -> Option<Option<String>> {
+    let url = format!("{}/api/version", state.backend_ollama_url);
+    let response = state.http_client.get(url).send().await.ok()?;
+    if !response.status().is_success() {
+        return None;
+    }
+    Some(response.json::<OllamaVersion>().await.ok().map(|v| v.version))
+}
+
+#[tauri::command]
+async fn get_system_status(
+    state: tauri::State<'_, AppState>,
+) -> Result<SystemStatus, CommandError> {
+    let ollama = fetch_ollama_version(&state).await;
+    let backend_up = check_backend_status(state.clone()).await.unwrap_or(false);
+    let starting = state.starting.load(Ordering::SeqCst);
+    Ok(SystemStatus {
+        ollama_up: ollama.is_some(),
+        ollama_health: service_health(&state.ollama_process, ollama.is_some(), starting).await,
+        ollama_version: ollama.flatten(),
+        backend_up,
+        backend_health: service_health(&state.backend_process, backend_up, starting).await,
+    })
+}
+
+/// Stops a child process. On Unix it first gets a SIGTERM and
+/// `GRACEFUL_SHUTDOWN_TIMEOUT` to exit on its own, so Ollama and the backend
+/// can flush their state; only then is it killed outright.
+async fn kill_process(mut child: std::process::Child) {
+    #[cfg(unix)]
+    if let Ok(None) = child.try_wait() {
+        let pid = child.id() as libc::pid_t;
+        // SAFETY: `kill` has no memory-safety preconditions, and the child
+        // hasn't been reaped yet so the PID still refers to it
+        if unsafe { libc::kill(pid, libc::SIGTERM) } == 0 {
+            let deadline = std::time::Instant::now() + GRACEFUL_SHUTDOWN_TIMEOUT;
+            while std::time::Instant::now() < deadline {
+                if let Ok(Some(_)) = child.try_wait() {
+                    return;
+                }
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+            warn!("Process {} did not exit after SIGTERM, killing it", pid);
+        }
+    }
+
+    // The process may already have exited, in which case there is nothing to kill
+    if let Err(e) = child.kill() {
+        warn!("Failed to kill process {}: {}", child.id(), e);
+    }
+    let _ = child.wait();
+}
+
+async fn stop_process(process: &Mutex<Option<std::process::Child>>) {
+    // Taking the child out of the slot makes repeated shutdowns a no-op.
+    // The guard is dropped first so the grace period doesn't block the slot.
+    let child = process.lock().await.take();
+    if let Some(child) = child {
+        kill_process(child).await;
+    }
+}
+
+async fn stop_services(state: &AppState) {
+    state.shutting_down.store(true, Ordering::SeqCst);
+    stop_process(&state.backend_process).await;
+    stop_process(&state.ollama_process).await;
+    state.pid_file.clear();
+}
+
+#[tauri::command]
+fn get_bundled_ollama_version() -> Option<String> {
+    bundled_ollama_version().map(str::to_string)
+}
+
+fn bundled_ollama_version() -> Option<&'static str> {
+    Some(BUNDLED_OLLAMA_VERSION).filter(|version| !version.is_empty())
+}
+
+#[tauri::command]
+fn get_ollama_port(state: tauri::State<'_, AppState>) -> u16 {
+    state.ollama_port
+}
+
+/// Polls `check` every `poll.interval_ms` until it passes or
+/// `poll.max_attempts` attempts have been made, calling `on_tick` with the
+/// attempt number before each one.
+async fn wait_for<F, Fut>(mut check: F, poll: PollConfig, mut on_tick: impl FnMut(u64)) -> bool
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = bool>,
+{
+    for attempt in 1..=poll.max_attempts {
+        on_tick(attempt);
+        if check().await {
+            return true;
+        }
+        tokio::time::sleep(poll.interval()).await;
+    }
+    false
+}
+
+#[derive(Clone, serde::Serialize)]
+struct StartupProgress {
+    service: &'static str,
+    attempt: u64,
+    max: u64,
+}
+
+fn emit_startup_progress(app_handle: &AppHandle, service: &'static str, attempt: u64, max: u64) {
+    let _ = app_handle.emit_all("startup-progress", StartupProgress { service, attempt, max });
+}
+
+/// Stops the Ollama this app spawned, releasing the GPU memory it holds.
+#[tauri::command]
+async fn stop_ollama(
+    app: AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), CommandError> {
+    let Some(child) = state.ollama_process.lock().await.take() else {
+        return Err(CommandError::Conflict(
+            "Ollama was not started by this app, so it is left running".to_string(),
+        ));
+    };
+    info!("Stopping Ollama on request");
+    kill_process(child).await;
+    state.pid_file.forget("ollama");
+    let _ = app.emit_all("ollama-stopped", ());
+    Ok(())
+}
+
+fn external_ollama_error(state: &AppState) -> CommandError {
+    CommandError::Conflict(format!(
+        "Ollama at {} is managed outside this app",
+        state.backend_ollama_url
+    ))
+}
+
+/// Starts Ollama again after `stop_ollama`, resolving once it is healthy.
+#[tauri::command]
+async fn start_ollama_command(
+    app: AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), CommandError> {
+    if state.dev_mode {
+        return Err(CommandError::Conflict(
+            "Ollama is managed externally in dev mode".to_string(),
+        ));
+    }
+    if state.external_ollama() {
+        return Err(external_ollama_error(&state));
+    }
+    if state.ollama_process.lock().await.is_some() {
+        return Err(CommandError::Conflict("Ollama is already running".to_string()));
+    }
+    if check_ollama_status(state.clone()).await.unwrap_or(false) {
+        return Err(CommandError::Conflict(
+            "Another Ollama is already running on this port".to_string(),
+        ));
+    }
+    {
+        let mut ollama = state.ollama_process.lock().await;
+        if ollama.is_some() {
+            return Err(CommandError::Conflict("Ollama is already running".to_string()));
+        }
+        let child = start_ollama(&state)
+            .await
+            .map_err(|e| format!("Failed to start Ollama: {}", e))?;
+        *ollama = Some(child);
+    }
+
+    let healthy = wait_for(
+        || async { check_ollama_status(state.clone()).await.unwrap_or(false) },
+        state.ollama_poll,
+        |_| {},
+    )
+    .await;
+    if !healthy {
+        return Err(CommandError::Timeout(format!(
+            "Ollama started but did not become healthy within {:?}",
+            state.ollama_poll.total()
+        )));
+    }
+    let _ = app.emit_all("ollama-started", ());
+    Ok(())
+}
+
+/// The kinds of state the app keeps on disk
+#[derive(Clone, Copy)]
+enum AppDir {
+    Config,
+    Data,
+    Logs,
+}
+
+/// Base directory of a portable install, from `OWUI_DATA_DIR`. Models live
+/// under it too instead of in Ollama's usual place.
+fn portable_dir() -> Option<PathBuf> {
+    std::env::var_os("OWUI_DATA_DIR")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Where the app keeps `kind` of state: a subdirectory of `OWUI_DATA_DIR`
+/// for portable installs, otherwise the platform's app directories.
+fn data_dir(tauri_config: &tauri::Config, kind: AppDir) -> PathBuf {
+    if let Some(base) = portable_dir() {
+        return base.join(match kind {
+            AppDir::Config => "config",
+            AppDir::Data => "data",
+            AppDir::Logs => "logs",
+        });
+    }
+    let dir = match kind {
+        AppDir::Config => tauri::api::path::app_config_dir(tauri_config),
+        AppDir::Logs => {
+            return tauri::api::path::app_log_dir(tauri_config)
+                .unwrap_or_else(|| std::env::temp_dir().join("open-webui-logs"))
+        }
+        AppDir::Data => tauri::api::path::app_data_dir(tauri_config),
+    };
+    dir.unwrap_or_else(std::env::temp_dir)
+}
+
+/// Where Ollama keeps its models: the configured directory, then
+/// `OLLAMA_MODELS`, then the portable install's models directory, then
+/// Ollama's own default under the home directory.
+fn models_dir(state: &AppState) -> Option<PathBuf> {
+    if let Some(dir) = state.config.lock().unwrap().models_dir.clone() {
+        return Some(PathBuf::from(dir));
+    }
+    if let Some(dir) = std::env::var_os("OLLAMA_MODELS") {
+        return Some(PathBuf::from(dir));
+    }
+    if let Some(dir) = portable_dir() {
+        return Some(dir.join("models"));
+    }
+    directories::BaseDirs::new().map(|dirs| dirs.home_dir().join(".ollama").join("models"))
+}
+
+#[tauri::command]
+fn get_models_dir(state: tauri::State<'_, AppState>) -> Option<String> {
+    models_dir(&state).map(|dir| dir.to_string_lossy().into_owned())
+}
+
+/// Moves Ollama's model storage to `path`, restarting an Ollama this app
+/// spawned so it picks the new location up. The directory must already
+/// exist and be writable.
+#[tauri::command]
+async fn set_models_dir(
+    app: AppHandle,
+    state: tauri::State<'_, AppState>,
+    path: String,
+) -> Result<(), CommandError> {
+    let dir = PathBuf::from(&path);
+    if !dir.is_dir() {
+        return Err(CommandError::InvalidInput(format!(
+            "{} is not an existing directory",
+            path
+        )));
+    }
+    // Permission bits don't tell the whole story (ACLs, read-only mounts),
+    // so try writing
+    let probe = dir.join(".open-webui-write-test");
+    fs::write(&probe, b"")
+        .and_then(|_| fs::remove_file(&probe))
+        .map_err(|e| CommandError::InvalidInput(format!("{} is not writable: {}", path, e)))?;
+
+    {
+        let mut config = state.config.lock().unwrap();
+        config.models_dir = Some(path.clone());
+        config.save(&state.config_path).map_err(CommandError::Config)?;
+    }
+    info!("Models directory set to {}", path);
+
+    if state.ollama_process.lock().await.is_some() {
+        restart_ollama(state.clone()).await?;
+    }
+    let _ = app.emit_all("models-dir-changed", path);
+    Ok(())
+}
+
+/// Sets how long Ollama keeps idle models loaded (`None` for its default),
+/// restarting an Ollama this app spawned to apply it.
+#[tauri::command]
+async fn set_keep_alive(
+    state: tauri::State<'_, AppState>,
+    value: Option<String>,
+) -> Result<(), CommandError> {
+    let value = value
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty());
+    if let Some(value) = &value {
+        config::validate_keep_alive(value).map_err(CommandError::InvalidInput)?;
+    }
+    {
+        let mut config = state.config.lock().unwrap();
+        config.model_keep_alive = value.clone();
+        config.save(&state.config_path).map_err(CommandError::Config)?;
+    }
+    info!("Model keep-alive set to {}", value.as_deref().unwrap_or("Ollama's default"));
+
+    if state.ollama_process.lock().await.is_some() {
+        restart_ollama(state.clone()).await?;
+    }
+    Ok(())
+}
+
+/// Sets the backend's log level. The backend only reads it at startup, so
+/// this takes effect on its next restart, announced with
+/// `backend-restart-required`.
+#[tauri::command]
+fn set_backend_log_level(
+    app: AppHandle,
+    state: tauri::State<'_, AppState>,
+    level: String,
+) -> Result<(), CommandError> {
+    let level = level.trim().to_uppercase();
+    config::validate_backend_log_level(&level).map_err(CommandError::InvalidInput)?;
+    let mut config = state.config.lock().unwrap();
+    if config.backend_log_level.as_deref() == Some(level.as_str()) {
+        return Ok(());
+    }
+    config.backend_log_level = Some(level.clone());
+    config.save(&state.config_path).map_err(CommandError::Config)?;
+    let _ = app.emit_all("backend-restart-required", level);
+    Ok(())
+}
+
+/// Sets one of the whitelisted `OLLAMA_FLAGS` for the spawned Ollama. Takes
+/// effect on its next restart, announced with `ollama-restart-required`.
+#[tauri::command]
+fn set_ollama_flag(
+    app: AppHandle,
+    state: tauri::State<'_, AppState>,
+    key: String,
+    value: String,
+) -> Result<(), CommandError> {
+    config::validate_ollama_flag(&key).map_err(CommandError::InvalidInput)?;
+    let value = value.trim().to_string();
+    if value.is_empty() || value.contains('\0') {
+        return Err(CommandError::InvalidInput(format!("Invalid value for {}", key)));
+    }
+    let mut config = state.config.lock().unwrap();
+    if config.ollama_flags.get(&key) == Some(&value) {
+        return Ok(());
+    }
+    config.ollama_flags.insert(key.clone(), value);
+    config.save(&state.config_path).map_err(CommandError::Config)?;
+    let _ = app.emit_all("ollama-restart-required", key);
+    Ok(())
+}
+
+#[tauri::command]
+fn clear_ollama_flag(
+    app: AppHandle,
+    state: tauri::State<'_, AppState>,
+    key: String,
+) -> Result<(), CommandError> {
+    let mut config = state.config.lock().unwrap();
+    if config.ollama_flags.remove(&key).is_none() {
+        return Ok(());
+    }
+    config.save(&state.config_path).map_err(CommandError::Config)?;
+    let _ = app.emit_all("ollama-restart-required", key);
+    Ok(())
+}
+
+#[tauri::command]
+async fn restart_ollama(state: tauri::State<'_, AppState>) -> Result<(), CommandError> {
+    if state.dev_mode {
+        return Err(CommandError::Conflict(
+            "Ollama is managed externally in dev mode".to_string(),
+        ));
+    }
+    if state.external_ollama() {
+        return Err(external_ollama_error(&state));
+    }
+    // Graceful shutdown can take a while, so don't hold the lock meanwhile
+    let previous = state.ollama_process.lock().await.take();
+    if let Some(child) = previous {
+        info!("Stopping Ollama for restart...");
+        kill_process(child).await;
+    }
+    let child = start_ollama(&state)
+        .await
+        .map_err(|e| format!("Failed to restart Ollama: {}", e))?;
+    let stale = state.ollama_process.lock().await.replace(child);
+    if let Some(stale) = stale {
+        kill_process(stale).await;
+    }
+
+    let healthy = wait_for(
+        || async { check_ollama_status(state.clone()).await.unwrap_or(false) },
+        state.ollama_poll,
+        |_| {},
+    )
+    .await;
+    if healthy {
+        return Ok(());
+    }
+    Err(CommandError::Timeout(format!(
+        "Ollama restarted but did not become healthy within {:?}",
+        state.ollama_poll.total()
+    )))
+}
+
+/// The URL the backend is actually served from, which differs from the
+/// configured one if its port was taken by another program.
+#[tauri::command]
+fn get_backend_url(state: tauri::State<'_, AppState>) -> String {
+    backend_base_url(&state.backend_url())
+}
+
+#[derive(serde::Serialize)]
+struct ServicePids {
+    ollama: Option<u32>,
+    backend: Option<u32>,
+}
+
+/// PIDs of the services this app spawned; `None` for anything it didn't
+/// start, such as a reused external Ollama.
+#[tauri::command]
+async fn get_service_pids(state: tauri::State<'_, AppState>) -> Result<ServicePids, CommandError> {
+    let ollama = state.ollama_process.lock().await.as_ref().map(|child| child.id());
+    let backend = state.backend_process.lock().await.as_ref().map(|child| child.id());
+    Ok(ServicePids { ollama, backend })
+}
+
+#[derive(serde::Serialize)]
+struct ProcUsage {
+    name: &'static str,
+    cpu_percent: f32,
+    memory_bytes: u64,
+}
+
+/// CPU and memory usage of the services this app spawned. The first call
+/// reports 0% CPU because there is no earlier sample to compare against.
+#[tauri::command]
+async fn get_resource_usage(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<ProcUsage>, CommandError> {
+    let pids = [
+        ("ollama", state.ollama_process.lock().await.as_ref().map(|child| child.id())),
+        ("backend", state.backend_process.lock().await.as_ref().map(|child| child.id())),
+    ];
+
+    let mut system = state.system.lock().unwrap();
+    let usage = pids
+        .into_iter()
+        .filter_map(|(name, pid)| {
+            let pid = sysinfo::Pid::from_u32(pid?);
+            if !system.refresh_process(pid) {
+                return None;
+            }
+            let process = system.process(pid)?;
+            Some(ProcUsage {
+                name,
+                cpu_percent: process.cpu_usage(),
+                memory_bytes: process.memory(),
+            })
+        })
+        .collect();
+    Ok(usage)
+}
+
+/// Called by the frontend once it has rendered, so the window is never
+/// revealed while the webview is still blank.
+#[tauri::command]
+fn frontend_ready(state: tauri::State<'_, AppState>) {
+    // A stored permit covers the frontend finishing before the services do
+    state.frontend_ready.notify_one();
+}
+
+/// Whether closing the window hides it, with the platform default applied
+/// when the user hasn't chosen.
+#[tauri::command]
+fn get_close_to_tray(state: tauri::State<'_, AppState>) -> bool {
+    state.config.lock().unwrap().close_to_tray()
+}
+
+#[tauri::command]
+fn set_close_to_tray(state: tauri::State<'_, AppState>, enabled: bool) -> Result<(), CommandError> {
+    let mut config = state.config.lock().unwrap();
+    config.close_to_tray = Some(enabled);
+    config.save(&state.config_path).map_err(CommandError::Config)
+}
+
+#[tauri::command]
+fn get_zoom(state: tauri::State<'_, AppState>) -> f64 {
+    state.config.lock().unwrap().zoom_factor
+}
+
+/// Zooms every window to `factor`, clamped to the supported range, and
+/// remembers it for the next launch. Returns the zoom actually applied.
+#[tauri::command]
+fn set_zoom(
+    app: AppHandle,
+    state: tauri::State<'_, AppState>,
+    factor: f64,
+) -> Result<f64, CommandError> {
+    if !factor.is_finite() {
+        return Err(CommandError::InvalidInput(format!("Invalid zoom factor {}", factor)));
+    }
+    let factor = factor.clamp(config::MIN_ZOOM, config::MAX_ZOOM);
+    let mut config = state.config.lock().unwrap();
+    config.zoom_factor = factor;
+    config.save(&state.config_path).map_err(CommandError::Config)?;
+    for window in app.windows().values() {
+        apply_zoom(window, factor);
+    }
+    Ok(factor)
+}
+
+/// Tauri 1 has no webview zoom API, so zoom the page itself. This has to be
+/// redone after every page load.
+fn apply_zoom(window: &tauri::Window, factor: f64) {
+    let script = format!("document.documentElement.style.zoom = '{}'", factor);
+    if let Err(e) = window.eval(&script) {
+        warn!("Failed to zoom the '{}' window: {}", window.label(), e);
+    }
+}
+
+#[tauri::command]
+fn is_first_run(state: tauri::State<'_, AppState>) -> bool {
+    state.config.lock().unwrap().first_run
+}
+
+/// Called by the frontend once onboarding (e.g. pulling a first model) has
+/// succeeded, so it isn't offered again.
+#[tauri::command]
+fn complete_first_run(state: tauri::State<'_, AppState>) -> Result<(), CommandError> {
+    let mut config = state.config.lock().unwrap();
+    config.first_run = false;
+    config.save(&state.config_path).map_err(CommandError::Config)
+}
+
+/// Whether the app spawns Ollama itself on the next launch.
+#[tauri::command]
+fn set_ollama_auto_start(
+    state: tauri::State<'_, AppState>,
+    enabled: bool,
+) -> Result<(), CommandError> {
+    let mut config = state.config.lock().unwrap();
+    config.ollama_auto_start = enabled;
+    config.save(&state.config_path).map_err(CommandError::Config)
+}
+
+#[tauri::command]
+fn get_config(state: tauri::State<'_, AppState>) -> Config {
+    state.config.lock().unwrap().clone()
+}
+
+/// Settings `update_config` writes as they are
+const PLAIN_SETTINGS: &[&str] = &[
+    "ollama_port",
+    "ollama_base_url",
+    "backend_url",
+    "use_tray",
+    "close_to_tray",
+    "ollama_poll",
+    "backend_poll",
+    "start_minimized",
+    "log_level",
+    "preload_model",
+    "backend_data_dir",
+    "python_venv",
+    "last_model",
+    "ollama_auto_start",
+    "first_run",
+    "dev_mode",
+];
+
+/// Settings that need more than a save to take effect (a restart, the window,
+/// the OS), with the command that changes each
+const SETTING_COMMANDS: &[(&str, &str)] = &[
+    ("models_dir", "set_models_dir"),
+    ("model_keep_alive", "set_keep_alive"),
+    ("zoom_factor", "set_zoom"),
+    ("default_num_ctx", "set_num_ctx"),
+    ("ollama_flags", "set_ollama_flag"),
+    ("backend_log_level", "set_backend_log_level"),
+    ("autostart", "set_autostart"),
+];
+
+/// Applies `patch`, an object holding only the settings to change, to the
+/// current config, then validates and persists the result. Merging under the
+/// lock keeps changes other commands made since the caller read the config.
+/// Only `PLAIN_SETTINGS` can be patched; the rest go through their own
+/// commands. `close_to_tray` applies immediately; the port, backend URL,
+/// polling and log level take effect on the next launch.
+#[tauri::command]
+fn update_config(
+    state: tauri::State<'_, AppState>,
+    patch: serde_json::Map<String, serde_json::Value>,
+) -> Result<Config, CommandError> {
+    let mut current = state.config.lock().unwrap();
+    let mut merged = serde_json::to_value(&*current)?;
+    let fields = merged
+        .as_object_mut()
+        .ok_or_else(|| CommandError::Other("The config is not an object".to_string()))?;
+    for (key, value) in patch {
+        if !PLAIN_SETTINGS.contains(&key.as_str()) {
+            let message = match SETTING_COMMANDS.iter().find(|(name, _)| *name == key) {
+                Some((_, command)) => format!("{} can only be changed with {}", key, command),
+                None => format!("Unknown setting {}", key),
+            };
+            return Err(CommandError::InvalidInput(message));
+        }
+        fields.insert(key, value);
+    }
+    let config: Config = serde_json::from_value(merged)
+        .map_err(|e| CommandError::InvalidInput(format!("Invalid setting: {}", e)))?;
+
+    config.validate().map_err(CommandError::InvalidInput)?;
+    config.save(&state.config_path).map_err(CommandError::Config)?;
+    *current = config.clone();
+    Ok(config)
+}
+
+#[tauri::command]
+fn get_log_path(state: tauri::State<'_, AppState>) -> String {
+    state.log_dir.to_string_lossy().into_owned()
+}
+
+/// Builds a shared HTTP client, falling back to reqwest's defaults (no
+/// timeouts) if the configured one can't be built, e.g. because the TLS
+/// backend failed to initialize.
+fn build_http_client(builder: reqwest::ClientBuilder) -> reqwest::Client {
+    builder.build().unwrap_or_else(|e| {
+        error!("Failed to build the HTTP client, using the default one: {}", e);
+        reqwest::Client::default()
+    })
+}
+
+/// Opens the log directory in the platform file manager, or shows its path
+/// if that fails.
+fn open_log_dir(app_handle: &AppHandle) {
+    let Some(state) = task_state(app_handle, "opening the log directory") else {
+        return;
+    };
+    let log_dir = &state.log_dir;
+    if let Err(e) = opener::open(log_dir) {
+        warn!("Failed to open {}: {}", log_dir.display(), e);
+        tauri::api::dialog::message(
+            main_window(app_handle).ok().as_ref(),
+            "Open WebUI logs",
+            format!("Logs are stored in {}", log_dir.display()),
+        );
+    }
+}
+
+/// Opens the version endpoint of the Ollama the backend talks to in the
+/// default browser, for poking at the raw API.
+#[tauri::command]
+fn open_ollama_api(app: AppHandle, state: tauri::State<'_, AppState>) -> Result<(), CommandError> {
+    let url = format!("{}/api/version", state.backend_ollama_url);
+    tauri::api::shell::open(&app.shell_scope(), &url, None)
+        .map_err(|e| CommandError::Other(format!("Failed to open {}: {}", url, e)))
+}
+
+/// Relaunches the app, e.g. after changing settings that only apply at
+/// startup. Both services are fully stopped first so the new instance can
+/// bind their ports.
+#[tauri::command]
+async fn restart_app(
+    app: AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), CommandError> {
+    info!("Restarting the app");
+    stop_services(&state).await;
+    single_instance::release(&state.instance_lock);
+    app.restart();
+    Ok(())
+}
+
+#[tauri::command]
+async fn shutdown_services(state: tauri::State<'_, AppState>) -> Result<(), CommandError> {
+    stop_services(&state).await;
+    Ok(())
+}
+
+/// The bundled Ollama for the architecture this binary runs as; universal
+/// macOS builds (`OLLAMA_UNIVERSAL_MACOS`) ship both Darwin binaries.
+fn get_ollama_path() -> Result<String, String> {
     let os = std::env::consts::OS;
     let arch = std::env::consts::ARCH;
     
-    match (os, arch) {
+    let path = match (os, arch) {
         ("macos", "aarch64") => "binaries/ollama-aarch64-apple-darwin",
         ("macos", "x86_64") => "binaries/ollama-x86_64-apple-darwin",
         ("linux", "aarch64") => "binaries/ollama-aarch64-unknown-linux",
         ("linux", "x86_64") => "binaries/ollama-x86_64-unknown-linux",
+        ("windows", "aarch64") => "binaries/ollama-aarch64-pc-windows.exe",
         ("windows", _) => "binaries/ollama.exe",
-        _ => panic!("Unsupported platform"),
-    }.to_string()
+        _ => {
+            return Err(format!(
+                "This platform ({}-{}) isn't supported for the bundled Ollama; \
+                 point OLLAMA_BASE_URL at an external instance",
+                os, arch
+            ))
+        }
+    };
+    Ok(path.to_string())
+}
+
+/// Removes the oldest `<service>-*.log` files so that, together with the
+/// file about to be created, at most `MAX_LOG_FILES` remain.
+fn rotate_logs(log_dir: &Path, service: &str) {
+    let prefix = format!("{}-", service);
+    let mut logs: Vec<PathBuf> = match fs::read_dir(log_dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name.starts_with(&prefix) && name.ends_with(".log"))
+            })
+            .collect(),
+        Err(_) => return,
+    };
+
+    // File names embed the launch timestamp, so they sort chronologically
+    logs.sort();
+    let excess = (logs.len() + 1).saturating_sub(MAX_LOG_FILES);
+    for path in logs.into_iter().take(excess) {
+        if let Err(e) = fs::remove_file(&path) {
+            warn!("Failed to remove old log {}: {}", path.display(), e);
+        }
+    }
+}
+
+fn create_log_file(log_dir: &Path, service: &str) -> Result<File, String> {
+    fs::create_dir_all(log_dir).map_err(|e| e.to_string())?;
+    rotate_logs(log_dir, service);
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let path = log_dir.join(format!("{}-{}.log", service, timestamp));
+    info!("Writing {} output to {}", service, path.display());
+
+    File::create(path).map_err(|e| e.to_string())
+}
+
+/// Opens a fresh log file for `service` and returns it as stdout/stderr handles.
+fn log_stdio(log_dir: &Path, service: &str) -> Result<(Stdio, Stdio), String> {
+    let stdout = create_log_file(log_dir, service)?;
+    let stderr = stdout.try_clone().map_err(|e| e.to_string())?;
+    Ok((Stdio::from(stdout), Stdio::from(stderr)))
 }
 
-async fn start_ollama() -> Result<std::process::Child, String> {
-    let binary_path = get_ollama_path();
-    println!("Starting Ollama from path: {}", binary_path);
+async fn start_ollama(state: &AppState) -> Result<std::process::Child, String> {
+    #[cfg(test)]
+    let binary_path = match &state.ollama_binary {
+        Some(path) => path.to_string_lossy().into_owned(),
+        None => get_ollama_path()?,
+    };
+    #[cfg(not(test))]
+    let binary_path = get_ollama_path()?;
+    let port = state.ollama_port;
+    info!("Starting Ollama from path: {} on port {}", binary_path, port);
+    let (stdout, stderr) = log_stdio(&state.log_dir, "ollama")?;
     
-    let process = Command::new(binary_path)
+    let options = &state.ollama_options;
+    let mut command = Command::new(&binary_path);
+    command
+        .arg("serve")
+        .args(&options.args)
+        .envs(options.env.iter().map(|(name, value)| (name, value)))
+        .env("OLLAMA_HOST", format!("127.0.0.1:{}", port));
+    {
+        let config = state.config.lock().unwrap();
+        if let Some(models_dir) = &config.models_dir {
+            command.env("OLLAMA_MODELS", models_dir);
+        } else if let Some(dir) = portable_dir() {
+            // Keep a portable install's models on the same drive, unless
+            // the environment already says where they are
+            if std::env::var_os("OLLAMA_MODELS").is_none() {
+                command.env("OLLAMA_MODELS", dir.join("models"));
+            }
+        }
+        if let Some(keep_alive) = &config.model_keep_alive {
+            command.env("OLLAMA_KEEP_ALIVE", keep_alive);
+        }
+        // A hand-edited config may hold anything, so check again here
+        let flags = config
+            .ollama_flags
+            .iter()
+            .filter(|(key, _)| config::validate_ollama_flag(key).is_ok());
+        command.envs(flags);
+    }
+    let process = command
+        .stdout(stdout)
+        .stderr(stderr)
         .spawn()
         .map_err(|e| {
-            eprintln!("Failed to start Ollama: {}", e);
+            error!("Failed to start Ollama: {}", e);
             e.to_string()
         })?;
+    state.pid_file.record("ollama", process.id(), &binary_path);
     
     Ok(process)
 }
 
-async fn start_backend() -> Result<std::process::Child, String> {
-    println!("Starting Python backend...");
+async fn start_backend(state: &AppState) -> Result<std::process::Child, String> {
+    let backend = &state.backend_command;
+    info!(
+        "Starting Python backend: {} {} (in {})",
+        backend.program,
+        backend.args.join(" "),
+        backend.dir.display()
+    );
+    let mut command = Command::new(&backend.program);
+    if let Some(venv) = &backend.venv {
+        let python = venv_python(venv);
+        if !python.is_file() {
+            return Err(format!(
+                "PYTHON_VENV {} has no Python interpreter at {}",
+                venv.display(),
+                python.display()
+            ));
+        }
+        // What `activate` would do, so tools the backend shells out to
+        // come from the venv as well
+        let bin_dir = python.parent().unwrap_or(venv);
+        let path = std::env::var_os("PATH").unwrap_or_default();
+        let path = std::env::join_paths(
+            std::iter::once(bin_dir.to_path_buf()).chain(std::env::split_paths(&path)),
+        )
+        .map_err(|e| e.to_string())?;
+        command.env("VIRTUAL_ENV", venv).env("PATH", path);
+    }
+    let (stdout, stderr) = log_stdio(&state.log_dir, "backend")?;
     
-    let process = Command::new("python3")
-        .args(&["-m", "backend.app"])
-        .env("OLLAMA_BASE_URL", "http://localhost:11434")
-        .current_dir("../")  // Move up one directory to find the backend module
+    fs::create_dir_all(&state.backend_data_dir).map_err(|e| {
+        format!(
+            "Failed to create the backend data directory {}: {}",
+            state.backend_data_dir.display(),
+            e
+        )
+    })?;
+    command.env("DATA_DIR", &state.backend_data_dir);
+    if let Some(level) = &state.config.lock().unwrap().backend_log_level {
+        command.env("GLOBAL_LOG_LEVEL", level);
+    }
+    let backend_url = state.backend_url();
+    let process = command
+        .args(&backend.args)
+        .env("OLLAMA_BASE_URL", &state.backend_ollama_url)
+        // Bind where the status checks expect to find the backend
+        .env("HOST", backend_url.host_str().unwrap_or("localhost"))
+        .env(
+            "PORT",
+            backend_url.port_or_known_default().unwrap_or(8080).to_string(),
+        )
+        .current_dir(&backend.dir)
+        .stdout(stdout)
+        .stderr(stderr)
         .spawn()
         .map_err(|e| {
-            eprintln!("Failed to start backend: {}", e);
+            error!("Failed to start backend: {}", e);
             e.to_string()
         })?;
+    state.pid_file.record("backend", process.id(), &backend.program);
     
     Ok(process)
 }
 
+async fn port_in_use(host: &str, port: u16) -> bool {
+    matches!(
+        tokio::time::timeout(
+            STATUS_CONNECT_TIMEOUT,
+            tokio::net::TcpStream::connect((host, port))
+        )
+        .await,
+        Ok(Ok(_))
+    )
+}
+
+/// Whether `url` serves our backend's health endpoint, as opposed to some
+/// unrelated program that happens to answer on the same port.
+async fn is_open_webui_backend(client: &reqwest::Client, url: &reqwest::Url) -> bool {
+    #[derive(serde::Deserialize)]
+    struct Health {
+        status: bool,
+    }
+
+    let Ok(response) = client.get(backend_health_url(url)).send().await else {
+        return false;
+    };
+    response.status().is_success()
+        && response.json::<Health>().await.is_ok_and(|health| health.status)
+}
+
+/// Makes sure the backend can bind its port before it is spawned. Returns
+/// `false` when one of our backends already answers there and can be reused;
+/// if another program holds the port, moves the backend to the next free one.
+async fn claim_backend_port(state: &AppState) -> Result<bool, String> {
+    let mut url = state.backend_url();
+    let host = url.host_str().unwrap_or("localhost").to_string();
+    let port = url.port_or_known_default().unwrap_or(8080);
+    if !port_in_use(&host, port).await {
+        return Ok(true);
+    }
+    if is_open_webui_backend(&state.http_client, &url).await {
+        info!("Reusing the backend already running at {}", backend_base_url(&url));
+        return Ok(false);
+    }
+
+    let last = port.saturating_add(BACKEND_PORT_SEARCH_RANGE);
+    let free = (port.saturating_add(1)..=last)
+        .find(|&candidate| std::net::TcpListener::bind((host.as_str(), candidate)).is_ok())
+        .ok_or_else(|| {
+            format!(
+                "Port {} is used by another program and no port up to {} is free",
+                port, last
+            )
+        })?;
+    warn!(
+        "Port {} is used by another program, starting the backend on port {}",
+        port, free
+    );
+    url.set_port(Some(free))
+        .map_err(|_| format!("Cannot set a port on {}", url))?;
+    *state.backend_url.lock().unwrap() = url;
+    Ok(true)
+}
+
+/// Looks up the main window, logging an error if it doesn't exist so callers
+/// can skip window work instead of panicking.
+fn main_window(app_handle: &AppHandle) -> Result<tauri::Window, String> {
+    app_handle.get_window(MAIN_WINDOW).ok_or_else(|| {
+        let message = format!("The '{}' window does not exist", MAIN_WINDOW);
+        error!("{}", message);
+        message
+    })
+}
+
+/// Focuses the settings window, creating it first if it isn't open.
+fn open_settings_window(app_handle: &AppHandle) {
+    if let Some(window) = app_handle.get_window(SETTINGS_WINDOW) {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+        return;
+    }
+    let result = tauri::WindowBuilder::new(
+        app_handle,
+        SETTINGS_WINDOW,
+        tauri::WindowUrl::App("settings".into()),
+    )
+    .title("Open WebUI Settings")
+    .inner_size(640.0, 560.0)
+    .min_inner_size(480.0, 400.0)
+    .build();
+    if let Err(e) = result {
+        error!("Failed to open the settings window: {}", e);
+    }
+}
+
+/// Outer position and inner size of a window, in physical pixels
+#[derive(serde::Serialize, serde::Deserialize)]
+struct WindowGeometry {
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+}
+
+fn window_state_path(app_handle: &AppHandle) -> PathBuf {
+    data_dir(&app_handle.config(), AppDir::Config).join(WINDOW_STATE_FILE)
+}
+
+fn save_window_geometry(window: &tauri::Window) -> Result<(), String> {
+    let position = window.outer_position().map_err(|e| e.to_string())?;
+    let size = window.inner_size().map_err(|e| e.to_string())?;
+    let geometry = WindowGeometry {
+        x: position.x,
+        y: position.y,
+        width: size.width,
+        height: size.height,
+    };
+
+    let path = window_state_path(&window.app_handle());
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let json = serde_json::to_string(&geometry).map_err(|e| e.to_string())?;
+    fs::write(path, json).map_err(|e| e.to_string())
+}
+
+/// Applies the saved geometry, moving the window back onto the primary
+/// monitor if it would otherwise open on a monitor that is no longer connected.
+fn restore_window_geometry(window: &tauri::Window) -> Result<(), String> {
+    let path = window_state_path(&window.app_handle());
+    let json = match fs::read_to_string(path) {
+        Ok(json) => json,
+        // Nothing saved yet, keep the default geometry
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e.to_string()),
+    };
+    let geometry: WindowGeometry = serde_json::from_str(&json).map_err(|e| e.to_string())?;
+
+    let monitors = window.available_monitors().map_err(|e| e.to_string())?;
+    let visible = monitors.iter().any(|monitor| {
+        let origin = monitor.position();
+        let size = monitor.size();
+        geometry.x >= origin.x
+            && geometry.y >= origin.y
+            && i64::from(geometry.x) < i64::from(origin.x) + i64::from(size.width)
+            && i64::from(geometry.y) < i64::from(origin.y) + i64::from(size.height)
+    });
+    let position = if visible {
+        tauri::PhysicalPosition::new(geometry.x, geometry.y)
+    } else {
+        match window.primary_monitor().map_err(|e| e.to_string())? {
+            Some(monitor) => *monitor.position(),
+            None => tauri::PhysicalPosition::new(0, 0),
+        }
+    };
+
+    window
+        .set_size(tauri::PhysicalSize::new(geometry.width, geometry.height))
+        .map_err(|e| e.to_string())?;
+    window.set_position(position).map_err(|e| e.to_string())
+}
+
+/// `AppState` for background tasks, which can race app setup or teardown.
+/// A missing state is logged so the caller can bail instead of panicking.
+fn task_state<'a>(app_handle: &'a AppHandle, task: &str) -> Option<tauri::State<'a, AppState>> {
+    let state = app_handle.try_state::<AppState>();
+    if state.is_none() {
+        error!("App state is not available, skipping {}", task);
+    }
+    state
+}
+
+/// Stops both services, then exits the app. A `shutting-down` event lets the
+/// UI show progress meanwhile, since a graceful stop can take a few seconds;
+/// the window stays as it is until the children are gone.
+fn quit(app_handle: &AppHandle) {
+    let app_handle = app_handle.clone();
+    let _ = app_handle.emit_all("shutting-down", ());
+    tauri::async_runtime::spawn(async move {
+        // Make sure no children outlive the app
+        if let Some(state) = task_state(&app_handle, "service shutdown") {
+            stop_services(&state).await;
+        }
+        app_handle.exit(0);
+    });
+}
+
+/// Resolves on SIGINT or SIGTERM (Ctrl+C on Windows).
+async fn termination_signal() -> std::io::Result<&'static str> {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        let mut interrupt = signal(SignalKind::interrupt())?;
+        let mut terminate = signal(SignalKind::terminate())?;
+        tokio::select! {
+            _ = interrupt.recv() => Ok("SIGINT"),
+            _ = terminate.recv() => Ok("SIGTERM"),
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        tokio::signal::ctrl_c().await?;
+        Ok("Ctrl+C")
+    }
+}
+
+/// Quits through the normal shutdown path when the process is told to
+/// terminate, e.g. from a terminal during development, so the children
+/// don't outlive it.
+async fn quit_on_signal(app_handle: AppHandle) {
+    match termination_signal().await {
+        Ok(signal) => {
+            info!("Received {}, shutting down", signal);
+            quit(&app_handle);
+        }
+        Err(e) => error!("Failed to install termination signal handlers: {}", e),
+    }
+}
+
+/// Delay before the given (zero-based) restart attempt: 1s, 2s, 4s… capped at 30s.
+fn restart_backoff(attempt: u32) -> Duration {
+    Duration::from_secs(1u64 << attempt.min(5)).min(MAX_RESTART_BACKOFF)
+}
+
+/// Watches the backend child and restarts it with exponential backoff if it
+/// exits on its own. Emits `backend-restarted` after each successful restart
+/// and `backend-failed` once `backend_max_restarts` attempts are used up,
+/// after which it idles until a backend is started again (e.g. by
+/// `retry_backend_start`).
+async fn supervise_backend(app_handle: AppHandle) {
+    let Some(state) = task_state(&app_handle, "backend supervision") else {
+        return;
+    };
+    run_backend_supervisor(&state, |event, attempts| {
+        let _ = app_handle.emit_all(event, attempts);
+    })
+    .await;
+}
+
+/// The loop behind `supervise_backend`, reporting its events through `emit`
+/// so it runs without an app. Returns once the app is shutting down.
+async fn run_backend_supervisor(state: &AppState, emit: impl Fn(&'static str, u32)) {
+    let mut attempts = 0;
+
+    loop {
+        tokio::time::sleep(SUPERVISOR_INTERVAL).await;
+        if state.shutting_down.load(Ordering::SeqCst) {
+            return;
+        }
+
+        let exit_status = {
+            let mut backend = state.backend_process.lock().await;
+            match backend.as_mut().map(|child| child.try_wait()) {
+                Some(Ok(Some(status))) => {
+                    backend.take();
+                    Some(status)
+                }
+                _ => None,
+            }
+        };
+
+        let Some(status) = exit_status else {
+            // A healthy backend earns back its full restart budget
+            if attempts > 0 && ping_backend(state).await.is_ok() {
+                attempts = 0;
+            }
+            continue;
+        };
+        warn!("Backend exited unexpectedly ({})", status);
+
+        loop {
+            if attempts >= state.backend_max_restarts {
+                error!("Giving up on backend after {} restart attempts", attempts);
+                emit("backend-failed", attempts);
+                attempts = 0;
+                break;
+            }
+
+            tokio::time::sleep(restart_backoff(attempts)).await;
+            attempts += 1;
+            if state.shutting_down.load(Ordering::SeqCst) {
+                return;
+            }
+
+            match start_backend(state).await {
+                Ok(backend_process) => {
+                    *state.backend_process.lock().await = Some(backend_process);
+                    info!("Backend restarted (attempt {})", attempts);
+                    emit("backend-restarted", attempts);
+                    break;
+                }
+                Err(e) => warn!("Backend restart attempt {} failed: {}", attempts, e),
+            }
+        }
+    }
+}
+
+/// Watches for an Ollama this app spawned that is still running but has
+/// stopped answering. After `ollama_hang_threshold` consecutive failed
+/// health checks it is force-restarted and `ollama-hang-detected` emitted.
+async fn supervise_ollama(app_handle: AppHandle) {
+    let Some(state) = task_state(&app_handle, "Ollama supervision") else {
+        return;
+    };
+    if state.ollama_hang_threshold == 0 {
+        return;
+    }
+    let mut failures = 0;
+
+    loop {
+        tokio::time::sleep(SUPERVISOR_INTERVAL).await;
+        if state.shutting_down.load(Ordering::SeqCst) {
+            return;
+        }
+
+        let running = state
+            .ollama_process
+            .lock()
+            .await
+            .as_mut()
+            .is_some_and(|child| matches!(child.try_wait(), Ok(None)));
+        // Only a live process can hang; a slow start isn't one either
+        if !running || state.starting.load(Ordering::SeqCst) {
+            failures = 0;
+            continue;
+        }
+        if check_ollama_status(state.clone()).await.unwrap_or(false) {
+            failures = 0;
+            continue;
+        }
+
+        failures += 1;
+        if failures < state.ollama_hang_threshold {
+            continue;
+        }
+        warn!("Ollama is running but failed {} health checks in a row, restarting it", failures);
+        let _ = app_handle.emit_all("ollama-hang-detected", failures);
+        failures = 0;
+        if let Err(e) = restart_ollama(state.clone()).await {
+            error!("Failed to restart hung Ollama: {}", e);
+        }
+    }
+}
+
+fn status_title(service: &str, up: bool) -> String {
+    format!("{}: {}", service, if up { "● Running" } else { "○ Stopped" })
+}
+
+/// Whether a system tray can be shown. Linux trays need an appindicator
+/// library, which minimal desktops may lack; elsewhere one always exists.
+fn tray_available() -> bool {
+    #[cfg(target_os = "linux")]
+    {
+        ["libayatana-appindicator3.so.1", "libappindicator3.so.1"]
+            .iter()
+            .any(|name| {
+                let name = std::ffi::CString::new(*name).unwrap_or_default();
+                // SAFETY: `name` is a valid C string, and the handle is only
+                // used to close the library again
+                unsafe {
+                    let handle = libc::dlopen(name.as_ptr(), libc::RTLD_LAZY);
+                    if handle.is_null() {
+                        return false;
+                    }
+                    libc::dlclose(handle);
+                    true
+                }
+            })
+    }
+    #[cfg(not(target_os = "linux"))]
+    true
+}
+
+/// Tray hover text summarizing both services
+fn tray_tooltip(starting: bool, ollama_up: bool, backend_up: bool) -> String {
+    if starting && !(ollama_up && backend_up) {
+        return "Open WebUI — Starting services…".to_string();
+    }
+    let status = |up| if up { "up" } else { "down" };
+    format!(
+        "Open WebUI — Ollama {}, Backend {}",
+        status(ollama_up),
+        status(backend_up)
+    )
+}
+
+/// Sets the tray tooltip; Linux trays don't support one, so it's skipped there.
+fn set_tray_tooltip(tray: &tauri::SystemTrayHandle, tooltip: &str) {
+    #[cfg(not(target_os = "linux"))]
+    if let Err(e) = tray.set_tooltip(tooltip) {
+        warn!("Failed to set the tray tooltip: {}", e);
+    }
+    #[cfg(target_os = "linux")]
+    let _ = (tray, tooltip);
+}
+
+/// Debounced up/down state of a single service
+struct HealthTracker {
+    service: &'static str,
+    confirmed: Option<bool>,
+    streak: u32,
+}
+
+impl HealthTracker {
+    fn new(service: &'static str) -> Self {
+        HealthTracker {
+            service,
+            confirmed: None,
+            streak: 0,
+        }
+    }
+
+    /// Records a poll result and returns the new state on a confirmed
+    /// transition. The first confirmed state is only recorded, not reported.
+    fn observe(&mut self, up: bool) -> Option<bool> {
+        if self.confirmed == Some(up) {
+            self.streak = 0;
+            return None;
+        }
+        self.streak += 1;
+        if self.streak < HEALTH_DEBOUNCE_POLLS {
+            return None;
+        }
+        self.streak = 0;
+        self.confirmed.replace(up).map(|_| up)
+    }
+
+    fn emit_transition(&mut self, app_handle: &AppHandle, up: bool) {
+        if let Some(up) = self.observe(up) {
+            let event = if up { "service-up" } else { "service-down" };
+            info!("{}: {}", event, self.service);
+            let _ = app_handle.emit_all(event, self.service);
+        }
+    }
+}
+
+/// Overall service health shown by the tray icon
+#[derive(Clone, Copy, PartialEq)]
+enum TrayHealth {
+    Starting,
+    Healthy,
+    Down,
+}
+
+impl TrayHealth {
+    fn icon(self) -> tauri::Icon {
+        let bytes: &[u8] = match self {
+            TrayHealth::Starting => include_bytes!("../icons/tray-starting.png"),
+            TrayHealth::Healthy => include_bytes!("../icons/tray-healthy.png"),
+            TrayHealth::Down => include_bytes!("../icons/tray-down.png"),
+        };
+        tauri::Icon::Raw(bytes.to_vec())
+    }
+}
+
+/// Periodically checks both services, keeping the tray's status items (if
+/// there is a tray) in sync and notifying the frontend when a service goes down or comes back.
+async fn watch_health(app_handle: AppHandle) {
+    let Some(state) = task_state(&app_handle, "health monitoring") else {
+        return;
+    };
+    let mut ollama = HealthTracker::new("ollama");
+    let mut backend = HealthTracker::new("backend");
+    let mut tray_health = None;
+    let mut tooltip = String::new();
+
+    loop {
+        let ollama_up = check_ollama_status(state.clone()).await.unwrap_or(false);
+        let backend_up = check_backend_status(state.clone()).await.unwrap_or(false);
+
+        if state.use_tray {
+            let tray = app_handle.tray_handle();
+            let _ = tray
+                .get_item("ollama_status")
+                .set_title(status_title("Ollama", ollama_up));
+            let _ = tray
+                .get_item("backend_status")
+                .set_title(status_title("Backend", backend_up));
+
+            let starting = state.starting.load(Ordering::SeqCst);
+            let new_tooltip = tray_tooltip(starting, ollama_up, backend_up);
+            if new_tooltip != tooltip {
+                set_tray_tooltip(&tray, &new_tooltip);
+                tooltip = new_tooltip;
+            }
+
+            let health = if ollama_up && backend_up {
+                TrayHealth::Healthy
+            } else if starting {
+                TrayHealth::Starting
+            } else {
+                TrayHealth::Down
+            };
+            if tray_health != Some(health) {
+                // Colored icons would be flattened if left as a macOS template image
+                #[cfg(target_os = "macos")]
+                let _ = tray.set_icon_as_template(false);
+                let _ = tray.set_icon(health.icon());
+                tray_health = Some(health);
+            }
+        }
+
+        ollama.emit_transition(&app_handle, ollama_up);
+        backend.emit_transition(&app_handle, backend_up);
+
+        tokio::time::sleep(HEALTH_POLL_INTERVAL).await;
+    }
+}
+
+/// Brings up Ollama and the backend in order, then shows the main window.
+async fn start_services(app_handle: AppHandle) {
+    let Some(state) = task_state(&app_handle, "service startup") else {
+        return;
+    };
+
+    if state.dev_mode {
+        attach_services(&app_handle, &state).await;
+    } else {
+        spawn_services(&app_handle, &state).await;
+    }
+
+    if tokio::time::timeout(FRONTEND_READY_TIMEOUT, state.frontend_ready.notified())
+        .await
+        .is_err()
+    {
+        warn!(
+            "Frontend did not report ready within {} seconds, showing the window anyway",
+            FRONTEND_READY_TIMEOUT.as_secs()
+        );
+    }
+
+    // Show the window once both services and the frontend are ready, unless
+    // launched into the tray; the tray's Open item reveals it then
+    if state.start_hidden {
+        info!("Started hidden, the window stays in the tray");
+    } else if state.window_shown.swap(true, Ordering::SeqCst) {
+        info!("The window is already showing");
+    } else if let Ok(window) = main_window(&app_handle) {
+        if let Err(e) = window.show() {
+            error!("Failed to show the main window: {}", e);
+        }
+    }
+    state.starting.store(false, Ordering::SeqCst);
+
+    if state.config.lock().unwrap().first_run {
+        let _ = app_handle.emit_all("show-onboarding", ());
+    }
+    models::emit_default_options(&app_handle, &state.config.lock().unwrap());
+    models::restore_last_model(&app_handle, &state).await;
+}
+
+/// Dev mode: waits for the manually started Ollama and backend without
+/// spawning or supervising anything, so quitting leaves them running.
+async fn attach_services(app_handle: &AppHandle, state: &tauri::State<'_, AppState>) {
+    info!("Dev mode: attaching to externally run services");
+
+    if !wait_for(
+        || async { check_ollama_status(state.clone()).await.unwrap_or(false) },
+        state.ollama_poll,
+        |attempt| {
+            emit_startup_progress(app_handle, "ollama", attempt, state.ollama_poll.max_attempts)
+        },
+    )
+    .await
+    {
+        warn!("Ollama is not reachable after {:?}", state.ollama_poll.total());
+    }
+    if !wait_for(
+        || async { check_backend_status(state.clone()).await.unwrap_or(false) },
+        state.backend_poll,
+        |attempt| {
+            emit_startup_progress(app_handle, "backend", attempt, state.backend_poll.max_attempts)
+        },
+    )
+    .await
+    {
+        warn!("Backend is not reachable after {:?}", state.backend_poll.total());
+    }
+}
+
+/// Starts (or reuses) Ollama and starts the supervised backend.
+async fn spawn_services(app_handle: &AppHandle, state: &tauri::State<'_, AppState>) {
+    if let Err(e) = ensure_ollama(app_handle, state).await {
+        error!("Failed to start Ollama: {}", e);
+        // Exiting straight away would look like a blank failed launch
+        let window = main_window(app_handle).ok();
+        tauri::api::dialog::blocking::message(window.as_ref(), "Failed to start Ollama", e);
+        app_handle.exit(1);
+        return;
+    }
+
+    tauri::async_runtime::spawn(supervise_ollama(app_handle.clone()));
+    tauri::async_runtime::spawn(supervise_backend(app_handle.clone()));
+
+    // A backend that fails to start is recoverable, so show the window
+    // either way and let the frontend offer a retry
+    match launch_backend(app_handle).await {
+        Ok(()) => {
+            tauri::async_runtime::spawn(models::preload_configured_model(app_handle.clone()));
+        }
+        Err(e) => {
+            error!("{}", e);
+            let _ = app_handle.emit_all("backend-start-failed", e);
+        }
+    }
+}
+
+/// Waits for Ollama to become healthy, first starting it unless it's already
+/// up, a child we spawned is still coming up, auto-start is off or it runs
+/// elsewhere. Only an Ollama that fails to spawn is an error; one that stays
+/// unhealthy is logged.
+async fn ensure_ollama(
+    app_handle: &AppHandle,
+    state: &tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    let startup = bring_up_ollama(state, |attempt| {
+        emit_startup_progress(app_handle, "ollama", attempt, state.ollama_poll.max_attempts)
+    })
+    .await?;
+    if startup == OllamaStartup::Unavailable {
+        let _ = app_handle.emit_all("ollama-unavailable", state.ollama_port);
+    }
+    Ok(())
+}
+
+/// How `bring_up_ollama` found Ollama
+#[derive(Debug, PartialEq, Eq)]
+enum OllamaStartup {
+    /// Already answering, so it is left to whoever started it
+    Reused,
+    Healthy,
+    /// Not ours to spawn, and none answered in time
+    Unavailable,
+    /// Spawned, but not healthy in time
+    Unhealthy,
+}
+
+/// The steps behind `ensure_ollama`, without an app so they can be tested;
+/// `on_tick` is called before each health check.
+async fn bring_up_ollama(
+    state: &AppState,
+    mut on_tick: impl FnMut(u64),
+) -> Result<OllamaStartup, String> {
+    let auto_start = state.config.lock().unwrap().ollama_auto_start;
+    let spawned_running = state
+        .ollama_process
+        .lock()
+        .await
+        .as_mut()
+        .is_some_and(|child| matches!(child.try_wait(), Ok(None)));
+    let healthy = || async { ping_ollama(state).await.is_ok() };
+
+    if healthy().await {
+        // Leave the slot empty so quitting never kills an Ollama we didn't start
+        info!("Reusing existing Ollama instance");
+        return Ok(OllamaStartup::Reused);
+    }
+    if !auto_start || state.external_ollama() {
+        // Ollama runs as a system service or on another host; wait for it
+        // but never spawn one
+        info!("Waiting for an external Ollama at {}", state.backend_ollama_url);
+        if wait_for(healthy, state.ollama_poll, &mut on_tick).await {
+            return Ok(OllamaStartup::Healthy);
+        }
+        warn!(
+            "No Ollama reachable at {} after {:?}",
+            state.backend_ollama_url,
+            state.ollama_poll.total()
+        );
+        return Ok(OllamaStartup::Unavailable);
+    }
+
+    // Start Ollama in the background, unless ours is just slow to come up
+    if !spawned_running {
+        let ollama_process = start_ollama(state).await?;
+        *state.ollama_process.lock().await = Some(ollama_process);
+    }
+    if wait_for(healthy, state.ollama_poll, &mut on_tick).await {
+        return Ok(OllamaStartup::Healthy);
+    }
+    warn!("Ollama did not become healthy within {:?}", state.ollama_poll.total());
+    Ok(OllamaStartup::Unhealthy)
+}
+
+/// Starts the backend unless one is already running, then waits for it to
+/// become healthy.
+async fn launch_backend(app_handle: &AppHandle) -> Result<(), CommandError> {
+    let state = app_handle
+        .try_state::<AppState>()
+        .ok_or_else(|| CommandError::Other("App state is not available".to_string()))?;
+    let poll = state.backend_poll;
+    if state.dev_mode {
+        return Err(CommandError::Conflict(
+            "The backend is managed externally in dev mode".to_string(),
+        ));
+    }
+
+    let running = state
+        .backend_process
+        .lock()
+        .await
+        .as_mut()
+        .is_some_and(|child| matches!(child.try_wait(), Ok(None)));
+    // Probing the port involves network round trips, so it happens without
+    // holding the lock other commands need
+    if !running && claim_backend_port(&state).await? {
+        let mut backend = state.backend_process.lock().await;
+        // The supervisor may have restarted it in the meantime
+        if !backend
+            .as_mut()
+            .is_some_and(|child| matches!(child.try_wait(), Ok(None)))
+        {
+            // Start the Python backend
+            let child = start_backend(&state)
+                .await
+                .map_err(|e| CommandError::Other(format!("Failed to start backend: {}", e)))?;
+            *backend = Some(child);
+        }
+    }
+
+    // Wait for the backend to answer, then to finish its own startup work
+    // such as database migrations
+    let reachable = AtomicBool::new(false);
+    let ready = wait_for(
+        || async {
+            let readiness = backend_readiness(&state).await;
+            if readiness != BackendReadiness::Unreachable && !reachable.swap(true, Ordering::SeqCst) {
+                let _ = app_handle.emit_all("backend-reachable", ());
+            }
+            readiness == BackendReadiness::Ready
+        },
+        poll,
+        |attempt| emit_startup_progress(app_handle, "backend", attempt, poll.max_attempts),
+    )
+    .await;
+    if ready {
+        let _ = app_handle.emit_all("backend-ready", ());
+        Ok(())
+    } else if reachable.load(Ordering::SeqCst) {
+        Err(CommandError::Timeout(format!(
+            "Backend did not finish starting up within {:?}",
+            poll.total()
+        )))
+    } else {
+        Err(CommandError::ServiceUnavailable(format!(
+            "Backend did not become healthy within {:?}",
+            poll.total()
+        )))
+    }
+}
+
+#[derive(PartialEq, Eq)]
+enum BackendReadiness {
+    Unreachable,
+    /// Answering, but still running migrations or other startup work
+    Reachable,
+    Ready,
+}
+
+async fn backend_readiness(state: &AppState) -> BackendReadiness {
+    #[derive(serde::Deserialize)]
+    struct Health {
+        status: bool,
+    }
+
+    let Ok(response) = state
+        .http_client
+        .get(backend_health_url(&state.backend_url()))
+        .send()
+        .await
+    else {
+        return BackendReadiness::Unreachable;
+    };
+    if !response.status().is_success() {
+        return BackendReadiness::Unreachable;
+    }
+    // Only the health route's own body counts; an HTML page or anything else
+    // that answers 200 is not a backend that has finished starting
+    match response.json::<Health>().await {
+        Ok(Health { status: true }) => BackendReadiness::Ready,
+        _ => BackendReadiness::Reachable,
+    }
+}
+
+/// Stops the current backend (if any) and brings up a fresh one.
+async fn restart_backend(app_handle: &AppHandle) -> Result<(), CommandError> {
+    let state = app_handle
+        .try_state::<AppState>()
+        .ok_or_else(|| CommandError::Other("App state is not available".to_string()))?;
+    // Taking the child out first keeps the supervisor from treating this as a crash
+    stop_process(&state.backend_process).await;
+    launch_backend(app_handle).await
+}
+
+#[tauri::command]
+async fn retry_backend_start(app: AppHandle) -> Result<(), CommandError> {
+    launch_backend(&app).await
+}
+
+/// Re-runs the startup sequence after a failed launch, with the same
+/// progress events. Services that are already up are left alone.
+#[tauri::command]
+async fn retry_startup(
+    app: AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), CommandError> {
+    if state.dev_mode {
+        return Err(CommandError::Conflict(
+            "Services are managed externally in dev mode".to_string(),
+        ));
+    }
+    if state.starting.swap(true, Ordering::SeqCst) {
+        return Err(CommandError::Conflict("Startup is already in progress".to_string()));
+    }
+    info!("Retrying startup");
+
+    let result: Result<(), CommandError> = async {
+        ensure_ollama(&app, &state).await?;
+        if !check_ollama_status(state.clone()).await.unwrap_or(false) {
+            return Err(CommandError::Timeout(format!(
+                "Ollama did not become healthy within {:?}",
+                state.ollama_poll.total()
+            )));
+        }
+        launch_backend(&app).await
+    }
+    .await;
+    state.starting.store(false, Ordering::SeqCst);
+    result
+}
+
+/// Logs to stderr and to a daily-rotated file in `log_dir`. The level filter
+/// comes from `OWUI_LOG`, then `RUST_LOG`, then the configured `log_level`,
+/// defaulting to `info`. The returned guard flushes the file writer and must
+/// be held until exit.
+fn init_logging(
+    log_dir: &Path,
+    log_level: &str,
+) -> Option<tracing_appender::non_blocking::WorkerGuard> {
+    let filter = std::env::var("OWUI_LOG")
+        .or_else(|_| std::env::var("RUST_LOG"))
+        .ok()
+        .and_then(|directives| EnvFilter::try_new(directives).ok())
+        .or_else(|| EnvFilter::try_new(log_level).ok())
+        .unwrap_or_else(|| EnvFilter::new(config::DEFAULT_LOG_LEVEL));
+
+    let file_appender = tracing_appender::rolling::Builder::new()
+        .rotation(tracing_appender::rolling::Rotation::DAILY)
+        .filename_prefix(logs::APP_LOG_PREFIX)
+        .filename_suffix("log")
+        .max_log_files(MAX_LOG_FILES)
+        .build(log_dir);
+    let (file_layer, guard) = match file_appender {
+        Ok(appender) => {
+            let (writer, guard) = tracing_appender::non_blocking(appender);
+            let layer = tracing_subscriber::fmt::layer()
+                .with_ansi(false)
+                .with_writer(writer);
+            (Some(layer), Some(guard))
+        }
+        Err(e) => {
+            eprintln!("Failed to open log file in {}: {}", log_dir.display(), e);
+            (None, None)
+        }
+    };
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(tracing_subscriber::fmt::layer().with_writer(std::io::stderr))
+        .with(file_layer)
+        .init();
+    guard
+}
+
 fn main() {
+    let context = tauri::generate_context!();
+    let log_dir = data_dir(context.config(), AppDir::Logs);
+
+    // Loaded before logging so the configured level applies from the start;
+    // load errors are reported once the subscriber exists
+    let config_path = Config::path(&data_dir(context.config(), AppDir::Config));
+    let loaded = Config::load(&config_path);
+    let log_level = match &loaded {
+        Ok(config) => config.log_level.clone(),
+        Err(_) => config::DEFAULT_LOG_LEVEL.to_string(),
+    };
+    let _log_guard = init_logging(&log_dir, &log_level);
+    let config = loaded.unwrap_or_else(|e| {
+        warn!("{}, using defaults", e);
+        Config::default()
+    });
+
+    if let Some(dir) = portable_dir() {
+        info!("Portable install, keeping all data under {}", dir.display());
+    }
+    let data_dir = data_dir(context.config(), AppDir::Data);
+    let instance_lock = single_instance::lock_path(&data_dir);
+    if single_instance::signal_existing(&instance_lock) {
+        info!("Open WebUI is already running, focused the existing window");
+        return;
+    }
+
+    // Only now is it certain the PIDs on record don't belong to a live instance
+    let pid_file = pids::PidFile::new(&data_dir);
+    pid_file.reap_stale();
+
+    let ollama_port = env_or("OLLAMA_PORT", config.ollama_port);
+    let backend_ollama_url = backend_ollama_url(config.ollama_base_url.as_deref(), ollama_port);
+    let backend_url = backend_url_from_env(&config.backend_url);
+    // STARTUP_TIMEOUT_SECS predates the poll settings and still overrides
+    // the total wait for both services
+    let (ollama_poll, backend_poll) = match std::env::var("STARTUP_TIMEOUT_SECS") {
+        Ok(_) => {
+            let secs = env_or("STARTUP_TIMEOUT_SECS", config.ollama_poll.total().as_secs());
+            (
+                config.ollama_poll.with_total_secs(secs),
+                config.backend_poll.with_total_secs(secs),
+            )
+        }
+        Err(_) => (config.ollama_poll, config.backend_poll),
+    };
+    let dev_mode = is_env_flag_set("DEV_MODE") || config.dev_mode;
+    let use_tray = config.use_tray && tray_available();
+    if config.use_tray && !use_tray {
+        warn!("No system tray is available, running without one");
+    }
+    // Without a tray a hidden window could never be brought back
+    let start_hidden = use_tray
+        && (std::env::args().any(|arg| arg == "--hidden") || config.start_minimized);
+    let python_venv = config.python_venv.clone();
+    let backend_data_dir = config
+        .backend_data_dir
+        .as_ref()
+        .map(PathBuf::from)
+        .unwrap_or_else(|| data_dir.join("backend"));
+
     let tray_menu = SystemTrayMenu::new()
+        .add_item(
+            CustomMenuItem::new("ollama_status".to_string(), status_title("Ollama", false))
+                .disabled(),
+        )
+        .add_item(
+            CustomMenuItem::new("backend_status".to_string(), status_title("Backend", false))
+                .disabled(),
+        )
+        .add_native_item(SystemTrayMenuItem::Separator)
         .add_item(CustomMenuItem::new("open".to_string(), "Open"))
+        .add_item(CustomMenuItem::new("settings".to_string(), "Settings"))
+        .add_item(CustomMenuItem::new("restart_backend".to_string(), "Restart Backend"))
+        .add_item(CustomMenuItem::new("open_logs".to_string(), "Open Logs"))
         .add_native_item(SystemTrayMenuItem::Separator)
         .add_item(CustomMenuItem::new("quit".to_string(), "Quit"));
 
-    let system_tray = SystemTray::new().with_menu(tray_menu);
+    let system_tray = SystemTray::new()
+        .with_menu(tray_menu)
+        .with_tooltip(&tray_tooltip(true, false, false));
 
-    tauri::Builder::default()
-        .system_tray(system_tray)
-        .on_system_tray_event(|app, event| match event {
-            SystemTrayEvent::MenuItemClick { id, .. } => match id.as_str() {
-                "quit" => {
-                    app.exit(0);
-                }
-                "open" => {
-                    let window = app.get_window("main").unwrap();
-                    window.show().unwrap();
+    let mut builder = tauri::Builder::default();
+    if use_tray {
+        builder = builder.system_tray(system_tray);
+    }
+    builder
+        .on_system_tray_event(|app, event| {
+            if let SystemTrayEvent::MenuItemClick { id, .. } = event {
+                match id.as_str() {
+                    "quit" => quit(app),
+                    "restart_backend" => {
+                        let app = app.clone();
+                        tauri::async_runtime::spawn(async move {
+                            // Disabled while restarting to prevent double-triggers
+                            let item = app.tray_handle().get_item("restart_backend");
+                            let _ = item.set_enabled(false);
+                            if let Err(e) = restart_backend(&app).await {
+                                error!("Failed to restart backend: {}", e);
+                            }
+                            let _ = item.set_enabled(true);
+                        });
+                    }
+                    "open" => {
+                        if let Ok(window) = main_window(app) {
+                            if let Err(e) = restore_window_geometry(&window) {
+                                warn!("Failed to restore window geometry: {}", e);
+                            }
+                            // Showing alone leaves a minimized or buried window
+                            // where it is; Windows in particular needs the focus
+                            let _ = window.unminimize();
+                            let _ = window.show();
+                            let _ = window.set_focus();
+                            if let Some(state) = task_state(app, "tracking the shown window") {
+                                state.window_shown.store(true, Ordering::SeqCst);
+                            }
+                        }
+                    }
+                    "settings" => open_settings_window(app),
+                    "open_logs" => open_log_dir(app),
+                    _ => {}
                 }
-                _ => {}
-            },
-            _ => {}
+            }
         })
+        .manage(models::Pulls::default())
+        .manage(chat::Chats::default())
+        .manage(models::DiskUsageCache::default())
+        .manage(updates::LatestRelease::default())
+        .manage(logs::LogFollower::default())
         .manage(AppState {
+            config: std::sync::Mutex::new(config),
+            config_path,
+            instance_lock: instance_lock.clone(),
             ollama_process: Arc::new(Mutex::new(None)),
             backend_process: Arc::new(Mutex::new(None)),
+            pid_file,
+            ollama_port,
+            #[cfg(test)]
+            ollama_binary: None,
+            ollama_options: OllamaOptions::from_env(),
+            backend_ollama_url,
+            backend_url: std::sync::Mutex::new(backend_url),
+            backend_command: BackendCommand::from_env(python_venv.as_deref()),
+            backend_data_dir,
+            log_dir,
+            http_client: build_http_client(
+                reqwest::Client::builder()
+                    .timeout(STATUS_TIMEOUT)
+                    .connect_timeout(STATUS_CONNECT_TIMEOUT),
+            ),
+            streaming_client: build_http_client(
+                reqwest::Client::builder().connect_timeout(STATUS_CONNECT_TIMEOUT),
+            ),
+            backend_max_restarts: env_or("BACKEND_MAX_RESTARTS", DEFAULT_BACKEND_MAX_RESTARTS),
+            ollama_hang_threshold: env_or("OLLAMA_HANG_THRESHOLD", DEFAULT_OLLAMA_HANG_THRESHOLD),
+            ollama_poll,
+            backend_poll,
+            frontend_ready: tokio::sync::Notify::new(),
+            starting: AtomicBool::new(true),
+            shutting_down: AtomicBool::new(false),
+            window_shown: AtomicBool::new(false),
+            use_tray,
+            dev_mode,
+            start_hidden,
+            system: std::sync::Mutex::new(sysinfo::System::new()),
         })
-        .setup(|app| {
+        .invoke_handler(tauri::generate_handler![
+            check_ollama_status,
+            check_backend_status,
+            get_ollama_port,
+            get_bundled_ollama_version,
+            get_backend_url,
+            get_system_status,
+            ping_services,
+            diagnostics::diagnose_connection,
+            diagnostics::get_gpu_info,
+            diagnostics::export_diagnostics,
+            get_service_pids,
+            get_resource_usage,
+            get_log_path,
+            open_ollama_api,
+            logs::read_recent_logs,
+            logs::subscribe_logs,
+            logs::unsubscribe_logs,
+            frontend_ready,
+            get_close_to_tray,
+            set_close_to_tray,
+            get_zoom,
+            set_zoom,
+            is_first_run,
+            complete_first_run,
+            set_ollama_auto_start,
+            autostart::get_autostart,
+            autostart::set_autostart,
+            get_config,
+            update_config,
+            models::list_ollama_models,
+            models::get_loaded_models,
+            models::pull_model,
+            models::cancel_pull,
+            chat::chat_stream,
+            chat::cancel_chat,
+            models::delete_model,
+            models::copy_model,
+            models::get_models_disk_usage,
+            models::set_preload_model,
+            models::set_num_ctx,
+            models::get_last_model,
+            models::set_last_model,
+            restart_ollama,
+            updates::check_ollama_update,
+            updates::check_backend_compatibility,
+            stop_ollama,
+            get_models_dir,
+            set_models_dir,
+            set_keep_alive,
+            set_ollama_flag,
+            clear_ollama_flag,
+            set_backend_log_level,
+            start_ollama_command,
+            retry_backend_start,
+            retry_startup,
+            shutdown_services,
+            restart_app
+        ])
+        .setup(move |app| {
             let app_handle = app.handle();
-            
-            tauri::async_runtime::spawn(async move {
-                // Start Ollama in the background
-                match start_ollama().await {
-                    Ok(ollama_process) => {
-                        let state: tauri::State<AppState> = app_handle.state();
-                        *state.ollama_process.lock().await = Some(ollama_process);
-                        
-                        // Wait for Ollama to start
-                        for _ in 0..30 {
-                            if check_ollama_status().await.unwrap_or(false) {
-                                break;
-                            }
-                            tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
-                        }
-                        
-                        // Start the Python backend
-                        match start_backend().await {
-                            Ok(backend_process) => {
-                                *state.backend_process.lock().await = Some(backend_process);
-                                
-                                // Wait for backend to start
-                                for _ in 0..30 {
-                                    if check_backend_status().await.unwrap_or(false) {
-                                        break;
-                                    }
-                                    tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
-                                }
-                                
-                                // Show the window once both services are ready
-                                if let Some(window) = app_handle.get_window("main") {
-                                    window.show().unwrap();
-                                }
-                            }
-                            Err(e) => {
-                                eprintln!("Failed to start backend: {}", e);
-                                app_handle.exit(1);
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        eprintln!("Failed to start Ollama: {}", e);
-                        app_handle.exit(1);
-                    }
+            if let Err(e) = single_instance::listen(app_handle.clone(), &instance_lock) {
+                error!("Failed to set up the single-instance guard: {}", e);
+            }
+            if let Ok(window) = main_window(&app_handle) {
+                if let Err(e) = restore_window_geometry(&window) {
+                    warn!("Failed to restore window geometry: {}", e);
                 }
-            });
+            }
+            if let Some(state) = task_state(&app_handle, "syncing launch at login") {
+                autostart::sync_config(&app_handle, &state);
+            }
+            tauri::async_runtime::spawn(quit_on_signal(app_handle.clone()));
+            tauri::async_runtime::spawn(watch_health(app_handle.clone()));
+            
+            tauri::async_runtime::spawn(start_services(app_handle));
             
             Ok(())
         })
+        .on_page_load(|window, _| {
+            let app_handle = window.app_handle();
+            let Some(state) = task_state(&app_handle, "restoring the zoom") else {
+                return;
+            };
+            let zoom = state.config.lock().unwrap().zoom_factor;
+            if zoom != 1.0 {
+                apply_zoom(&window, zoom);
+            }
+        })
         .on_window_event(|event| {
             if let tauri::WindowEvent::CloseRequested { api, .. } = event.event() {
-                event.window().hide().unwrap();
+                let window = event.window();
+                // Other windows, like settings, are simply destroyed
+                if window.label() != MAIN_WINDOW {
+                    return;
+                }
+                if let Err(e) = save_window_geometry(window) {
+                    warn!("Failed to save window geometry: {}", e);
+                }
+
+                let app_handle = window.app_handle();
+                // Without the state there is nothing to shut down, so let it close
+                let Some(state) = task_state(&app_handle, "handling the window close") else {
+                    return;
+                };
+                let close_to_tray = state.use_tray && state.config.lock().unwrap().close_to_tray();
+                // Either way the window stays open until any shutdown has finished
                 api.prevent_close();
+                if close_to_tray {
+                    if let Err(e) = window.hide() {
+                        error!("Failed to hide the window: {}", e);
+                    }
+                } else {
+                    quit(&app_handle);
+                }
             }
         })
-        .run(tauri::generate_context!())
+        .run(context)
         .expect("error while running tauri application");
 }
@@ -0,0 +1,500 @@
+//! Commands for managing the models installed in Ollama.
+
+use crate::config::{self, Config};
+use crate::error::CommandError;
+use crate::tasks::{self, InFlight};
+use crate::{models_dir, AppState};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+use tracing::{debug, info, warn};
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct ModelInfo {
+    name: String,
+    size: u64,
+    modified_at: String,
+}
+
+#[derive(serde::Deserialize)]
+struct TagsResponse {
+    #[serde(default)]
+    models: Vec<ModelInfo>,
+}
+
+/// Lists installed models sorted by name. An empty list means Ollama is up
+/// but has no models; an error means it couldn't be reached.
+#[tauri::command]
+pub async fn list_ollama_models(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<ModelInfo>, CommandError> {
+    let url = format!("{}/api/tags", state.backend_ollama_url);
+    let response = state
+        .http_client
+        .get(&url)
+        .send()
+        .await
+        .map_err(|e| {
+            CommandError::ServiceUnavailable(format!("Ollama is not reachable at {}: {}", url, e))
+        })?;
+    if !response.status().is_success() {
+        return Err(CommandError::Other(format!(
+            "Ollama failed to list models: {}",
+            response.status()
+        )));
+    }
+
+    let mut models = response
+        .json::<TagsResponse>()
+        .await
+        .map_err(|e| CommandError::Other(format!("Unexpected response from Ollama: {}", e)))?
+        .models;
+    models.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(models)
+}
+
+/// A model Ollama currently has in memory, from `/api/ps`
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct LoadedModel {
+    pub name: String,
+    /// Bytes of the model held in GPU memory; 0 when running on the CPU
+    #[serde(default)]
+    pub size_vram: u64,
+    /// When Ollama will unload it if it stays idle
+    expires_at: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+struct PsResponse {
+    #[serde(default)]
+    models: Vec<LoadedModel>,
+}
+
+pub(crate) async fn running_models(state: &AppState) -> Result<Vec<LoadedModel>, CommandError> {
+    let url = format!("{}/api/ps", state.backend_ollama_url);
+    let response = state
+        .http_client
+        .get(&url)
+        .send()
+        .await
+        .map_err(|e| {
+            CommandError::ServiceUnavailable(format!("Ollama is not reachable at {}: {}", url, e))
+        })?;
+    if !response.status().is_success() {
+        return Err(CommandError::Other(format!(
+            "Ollama failed to list running models: {}",
+            response.status()
+        )));
+    }
+    Ok(response
+        .json::<PsResponse>()
+        .await
+        .map_err(|e| CommandError::Other(format!("Unexpected response from Ollama: {}", e)))?
+        .models)
+}
+
+/// Models resident in Ollama's memory, so the UI can tell a warm model from
+/// one that is merely selected. Empty when nothing is loaded.
+#[tauri::command]
+pub async fn get_loaded_models(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<LoadedModel>, CommandError> {
+    running_models(&state).await
+}
+
+/// Removes an installed model and emits `model-deleted` so model lists can
+/// refresh.
+#[tauri::command]
+pub async fn delete_model(
+    app: AppHandle,
+    state: tauri::State<'_, AppState>,
+    cache: tauri::State<'_, DiskUsageCache>,
+    name: String,
+) -> Result<(), CommandError> {
+    let url = format!("{}/api/delete", state.backend_ollama_url);
+    let response = state
+        .http_client
+        .delete(&url)
+        .json(&serde_json::json!({ "name": name }))
+        .send()
+        .await
+        .map_err(|e| {
+            CommandError::ServiceUnavailable(format!("Ollama is not reachable at {}: {}", url, e))
+        })?;
+    let status = response.status();
+    if status == reqwest::StatusCode::NOT_FOUND {
+        return Err(CommandError::NotFound(format!("{} is not installed", name)));
+    }
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        // Ollama's own error doesn't say why, so point out the likely cause
+        let loaded = running_models(&state)
+            .await
+            .is_ok_and(|models| models.iter().any(|model| model.name == name));
+        if loaded {
+            return Err(CommandError::Conflict(format!(
+                "{} is in use; unload it before deleting",
+                name
+            )));
+        }
+        return Err(CommandError::Other(format!(
+            "Ollama failed to delete {}: {} {}",
+            name, status, body
+        )));
+    }
+
+    info!("Deleted model {}", name);
+    // The freed space should show up right away
+    *cache.0.lock().unwrap() = None;
+    let _ = app.emit_all("model-deleted", name);
+    Ok(())
+}
+
+/// Copies `source` to a new model named `destination`, the first step of
+/// customizing one, and emits `model-copied` with the new name.
+#[tauri::command]
+pub async fn copy_model(
+    app: AppHandle,
+    state: tauri::State<'_, AppState>,
+    cache: tauri::State<'_, DiskUsageCache>,
+    source: String,
+    destination: String,
+) -> Result<(), CommandError> {
+    let destination = destination.trim().to_string();
+    if destination.is_empty() {
+        return Err(CommandError::InvalidInput("The new model needs a name".to_string()));
+    }
+    // Ollama silently overwrites an existing model, so check first
+    let installed = list_ollama_models(state.clone()).await?;
+    if installed
+        .iter()
+        .any(|model| with_default_tag(&model.name) == with_default_tag(&destination))
+    {
+        return Err(CommandError::Conflict(format!("{} already exists", destination)));
+    }
+
+    let url = format!("{}/api/copy", state.backend_ollama_url);
+    let response = state
+        .http_client
+        .post(&url)
+        .json(&serde_json::json!({ "source": source, "destination": destination }))
+        .send()
+        .await
+        .map_err(|e| {
+            CommandError::ServiceUnavailable(format!("Ollama is not reachable at {}: {}", url, e))
+        })?;
+    let status = response.status();
+    if status == reqwest::StatusCode::NOT_FOUND {
+        return Err(CommandError::NotFound(format!("{} is not installed", source)));
+    }
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(CommandError::Other(format!(
+            "Ollama failed to copy {} to {}: {} {}",
+            source, destination, status, body
+        )));
+    }
+
+    info!("Copied model {} to {}", source, destination);
+    *cache.0.lock().unwrap() = None;
+    let _ = app.emit_all("model-copied", destination);
+    Ok(())
+}
+
+/// Ollama lists untagged names with `:latest`
+fn with_default_tag(name: &str) -> String {
+    if name.contains(':') {
+        name.to_string()
+    } else {
+        format!("{}:latest", name)
+    }
+}
+
+/// In-flight pulls by model name, so they can be cancelled
+pub struct Pulls(InFlight);
+
+impl Default for Pulls {
+    fn default() -> Self {
+        Pulls(InFlight::new("pull"))
+    }
+}
+
+/// One line of Ollama's NDJSON pull stream
+#[derive(serde::Deserialize)]
+struct PullStatus {
+    #[serde(default)]
+    status: String,
+    completed: Option<u64>,
+    total: Option<u64>,
+    error: Option<String>,
+}
+
+#[derive(Clone, serde::Serialize)]
+struct PullProgress {
+    name: String,
+    status: String,
+    completed: Option<u64>,
+    total: Option<u64>,
+}
+
+/// Downloads `name` into Ollama, re-emitting its progress lines as
+/// `model-pull-progress` events. Resolves once the pull finishes, fails or
+/// is cancelled with `cancel_pull`.
+#[tauri::command]
+pub async fn pull_model(
+    app: AppHandle,
+    pulls: tauri::State<'_, Pulls>,
+    name: String,
+) -> Result<(), CommandError> {
+    pulls.0.run(&name, stream_pull(app, name.clone())).await
+}
+
+/// Aborts an in-flight `pull_model`, dropping its request to Ollama.
+#[tauri::command]
+pub async fn cancel_pull(pulls: tauri::State<'_, Pulls>, name: String) -> Result<(), CommandError> {
+    pulls.0.cancel(&name).await
+}
+
+async fn stream_pull(app: AppHandle, name: String) -> Result<(), CommandError> {
+    let state = app
+        .try_state::<AppState>()
+        .ok_or_else(|| CommandError::Other("App state is not available".to_string()))?;
+    let url = format!("{}/api/pull", state.backend_ollama_url);
+    let response = state
+        .streaming_client
+        .post(&url)
+        .json(&serde_json::json!({ "name": name, "stream": true }))
+        .send()
+        .await
+        .map_err(|e| {
+            CommandError::ServiceUnavailable(format!("Ollama is not reachable at {}: {}", url, e))
+        })?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(CommandError::Other(format!(
+            "Ollama failed to pull {}: {} {}",
+            name, status, body
+        )));
+    }
+
+    tasks::for_each_line(response, |line| {
+        emit_pull_line(&app, &name, line)?;
+        Ok(false)
+    })
+    .await
+}
+
+fn emit_pull_line(app: &AppHandle, name: &str, line: &[u8]) -> Result<(), String> {
+    if line.iter().all(u8::is_ascii_whitespace) {
+        return Ok(());
+    }
+    let status: PullStatus = serde_json::from_slice(line)
+        .map_err(|e| format!("Unexpected pull progress from Ollama: {}", e))?;
+    if let Some(error) = status.error {
+        return Err(format!("Ollama failed to pull {}: {}", name, error));
+    }
+
+    let _ = app.emit_all(
+        "model-pull-progress",
+        PullProgress {
+            name: name.to_string(),
+            status: status.status,
+            completed: status.completed,
+            total: status.total,
+        },
+    );
+    Ok(())
+}
+
+/// How long a preloaded model stays in memory without being used
+const PRELOAD_KEEP_ALIVE: &str = "30m";
+
+/// Loads the configured `preload_model` into Ollama's memory with an empty
+/// generate request, emitting `model-preloaded` once it's ready. Models that
+/// aren't installed are skipped.
+pub async fn preload_configured_model(app: AppHandle) {
+    let Some(state) = app.try_state::<AppState>() else {
+        return;
+    };
+    let (model, options) = {
+        let config = state.config.lock().unwrap();
+        (config.preload_model.clone(), default_options(&config))
+    };
+    let Some(model) = model else {
+        return;
+    };
+
+    let url = format!("{}/api/generate", state.backend_ollama_url);
+    // Loading a large model can take a while, so no overall timeout
+    let response = state
+        .streaming_client
+        .post(&url)
+        .json(&serde_json::json!({
+            "model": model,
+            "keep_alive": PRELOAD_KEEP_ALIVE,
+            "options": options,
+        }))
+        .send()
+        .await;
+    match response {
+        Ok(response) if response.status().is_success() => {
+            info!("Preloaded model {}", model);
+            let _ = app.emit_all("model-preloaded", model);
+        }
+        Ok(response) if response.status() == reqwest::StatusCode::NOT_FOUND => {
+            debug!("Preload model {} is not installed, skipping", model);
+        }
+        Ok(response) => warn!("Failed to preload {}: {}", model, response.status()),
+        Err(e) => warn!("Failed to preload {}: {}", model, e),
+    }
+}
+
+#[tauri::command]
+pub fn set_preload_model(
+    state: tauri::State<'_, AppState>,
+    name: Option<String>,
+) -> Result<(), CommandError> {
+    let mut config = state.config.lock().unwrap();
+    config.preload_model = name.filter(|name| !name.trim().is_empty());
+    config.save(&state.config_path).map_err(CommandError::Config)
+}
+
+/// Context lengths most models are trained for top out around here
+const TYPICAL_MAX_NUM_CTX: u32 = 32 * 1024;
+
+/// Model options the frontend merges into its requests, since Ollama only
+/// takes them per request
+fn default_options(config: &Config) -> serde_json::Value {
+    let mut options = serde_json::Map::new();
+    if let Some(num_ctx) = config.default_num_ctx {
+        options.insert("num_ctx".to_string(), num_ctx.into());
+    }
+    options.into()
+}
+
+pub fn emit_default_options(app: &AppHandle, config: &Config) {
+    let _ = app.emit_all("default-options", default_options(config));
+}
+
+/// Sets the default context length (`None` for each model's own) and emits
+/// the new `default-options`.
+#[tauri::command]
+pub fn set_num_ctx(
+    app: AppHandle,
+    state: tauri::State<'_, AppState>,
+    n: Option<u32>,
+) -> Result<(), CommandError> {
+    if let Some(n) = n {
+        config::validate_num_ctx(n).map_err(CommandError::InvalidInput)?;
+        if n > TYPICAL_MAX_NUM_CTX {
+            warn!(
+                "num_ctx {} exceeds what most models support ({}); they may ignore it or run \
+                 out of memory",
+                n, TYPICAL_MAX_NUM_CTX
+            );
+        }
+    }
+    let mut config = state.config.lock().unwrap();
+    config.default_num_ctx = n;
+    config.save(&state.config_path).map_err(CommandError::Config)?;
+    emit_default_options(&app, &config);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_last_model(state: tauri::State<'_, AppState>) -> Option<String> {
+    state.config.lock().unwrap().last_model.clone()
+}
+
+/// Called by the frontend whenever the selected model changes.
+#[tauri::command]
+pub fn set_last_model(
+    state: tauri::State<'_, AppState>,
+    name: Option<String>,
+) -> Result<(), CommandError> {
+    let mut config = state.config.lock().unwrap();
+    config.last_model = name.filter(|name| !name.trim().is_empty());
+    config.save(&state.config_path).map_err(CommandError::Config)
+}
+
+/// Emits `restore-model` with the last selected model if it's still
+/// installed, and forgets it if it has been removed since.
+pub async fn restore_last_model(app: &AppHandle, state: &tauri::State<'_, AppState>) {
+    let Some(model) = state.config.lock().unwrap().last_model.clone() else {
+        return;
+    };
+    let models = match list_ollama_models(state.clone()).await {
+        Ok(models) => models,
+        // Without the list we can't tell, so keep it for next time
+        Err(e) => {
+            warn!("Could not check whether {} is still installed: {}", model, e);
+            return;
+        }
+    };
+
+    if models.iter().any(|installed| installed.name == model) {
+        let _ = app.emit_all("restore-model", model);
+        return;
+    }
+    info!("Last selected model {} is no longer installed", model);
+    let mut config = state.config.lock().unwrap();
+    config.last_model = None;
+    if let Err(e) = config.save(&state.config_path) {
+        warn!("Failed to save config: {}", e);
+    }
+}
+
+/// How long a computed disk usage is reused before walking the directory again
+const DISK_USAGE_TTL: Duration = Duration::from_secs(30);
+
+/// Last result of `get_models_disk_usage`, since walking a large models
+/// directory can be slow on spinning disks
+#[derive(Default)]
+pub struct DiskUsageCache(std::sync::Mutex<Option<(PathBuf, Instant, u64)>>);
+
+/// Total size in bytes of the files in the models directory, or 0 if it
+/// doesn't exist yet.
+#[tauri::command]
+pub async fn get_models_disk_usage(
+    state: tauri::State<'_, AppState>,
+    cache: tauri::State<'_, DiskUsageCache>,
+) -> Result<u64, CommandError> {
+    let dir = models_dir(&state).ok_or_else(|| "Could not resolve the models directory".to_string())?;
+    if let Some((cached_dir, at, bytes)) = &*cache.0.lock().unwrap() {
+        if *cached_dir == dir && at.elapsed() < DISK_USAGE_TTL {
+            return Ok(*bytes);
+        }
+    }
+
+    let walk_dir = dir.clone();
+    let bytes = tokio::task::spawn_blocking(move || dir_size(&walk_dir))
+        .await
+        .map_err(|e| e.to_string())?;
+    *cache.0.lock().unwrap() = Some((dir, Instant::now(), bytes));
+    Ok(bytes)
+}
+
+/// Sums file sizes under `dir`. Symlinks aren't followed, so links back up
+/// the tree can't cause loops, and unreadable entries are skipped.
+fn dir_size(dir: &Path) -> u64 {
+    let mut total = 0;
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            // `DirEntry::metadata` doesn't traverse symlinks
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            if metadata.is_dir() {
+                pending.push(entry.path());
+            } else if metadata.is_file() {
+                total += metadata.len();
+            }
+        }
+    }
+    total
+}
@@ -0,0 +1,113 @@
+//! Records the PIDs of spawned services in `pids.json` so that processes
+//! orphaned by a crash or force-quit can be cleaned up on the next launch.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+const PIDS_FILE: &str = "pids.json";
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct SpawnedProcess {
+    pid: u32,
+    /// Program the process was started from, used to avoid killing an
+    /// unrelated process that has since been given the same PID
+    program: String,
+}
+
+pub struct PidFile {
+    path: PathBuf,
+    processes: std::sync::Mutex<HashMap<String, SpawnedProcess>>,
+}
+
+impl PidFile {
+    pub fn new(data_dir: &Path) -> Self {
+        PidFile {
+            path: data_dir.join(PIDS_FILE),
+            processes: Default::default(),
+        }
+    }
+
+    /// Remembers that `service` now runs as `pid`, started from `program`.
+    pub fn record(&self, service: &str, pid: u32, program: &str) {
+        let mut processes = self.processes.lock().unwrap();
+        processes.insert(
+            service.to_string(),
+            SpawnedProcess {
+                pid,
+                program: program.to_string(),
+            },
+        );
+        let result = fs::create_dir_all(self.path.parent().unwrap_or(Path::new(".")))
+            .and_then(|_| fs::write(&self.path, serde_json::to_vec(&*processes)?));
+        if let Err(e) = result {
+            warn!("Failed to write {}: {}", self.path.display(), e);
+        }
+    }
+
+    /// Drops `service` once it has been stopped, so a later sweep doesn't
+    /// act on a PID that may since have been reused.
+    pub fn forget(&self, service: &str) {
+        let mut processes = self.processes.lock().unwrap();
+        if processes.remove(service).is_none() {
+            return;
+        }
+        if let Err(e) = fs::write(&self.path, serde_json::to_vec(&*processes).unwrap_or_default()) {
+            warn!("Failed to write {}: {}", self.path.display(), e);
+        }
+    }
+
+    /// Deletes the file once services have been stopped cleanly.
+    pub fn clear(&self) {
+        self.processes.lock().unwrap().clear();
+        if let Err(e) = fs::remove_file(&self.path) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                warn!("Failed to remove {}: {}", self.path.display(), e);
+            }
+        }
+    }
+
+    /// Kills processes left behind by a previous session that didn't shut
+    /// down cleanly, then forgets them.
+    pub fn reap_stale(&self) {
+        let Ok(json) = fs::read_to_string(&self.path) else {
+            return;
+        };
+        let stale: HashMap<String, SpawnedProcess> = serde_json::from_str(&json).unwrap_or_default();
+
+        let mut system = sysinfo::System::new();
+        for (service, spawned) in stale {
+            let pid = sysinfo::Pid::from_u32(spawned.pid);
+            if !system.refresh_process(pid) {
+                continue;
+            }
+            let Some(process) = system.process(pid) else {
+                continue;
+            };
+            if !is_same_program(process.name(), &spawned.program) {
+                continue;
+            }
+            info!("Killing {} (PID {}) left over from a previous session", service, spawned.pid);
+            if !process.kill() {
+                warn!("Failed to kill leftover {} (PID {})", service, spawned.pid);
+            }
+        }
+        self.clear();
+    }
+}
+
+/// Compares a running process's name against the program it was started
+/// from. Linux truncates process names to 15 bytes, so a prefix match counts.
+fn is_same_program(process_name: &str, program: &str) -> bool {
+    let normalize = |name: &str| name.trim_end_matches(".exe").to_lowercase();
+    let expected = Path::new(program)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .map(normalize)
+        .unwrap_or_default();
+    let actual = normalize(process_name);
+    !expected.is_empty()
+        && !actual.is_empty()
+        && (actual.starts_with(&expected) || expected.starts_with(&actual))
+}
@@ -0,0 +1,95 @@
+//! Keeps a single running copy of the app.
+//!
+//! The first instance listens on an ephemeral loopback port and records it in
+//! a lock file. Later launches connect to that port, ask the running instance
+//! to show its window and then exit instead of spawning duplicate services.
+
+use std::fs;
+use std::io::{self, Read, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use tauri::AppHandle;
+use tracing::{info, warn};
+
+const LOCK_FILE: &str = "instance.lock";
+const SHOW_REQUEST: &[u8] = b"open-webui:show";
+const SHOW_ACK: &[u8] = b"open-webui:ok";
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_millis(500);
+
+pub fn lock_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(LOCK_FILE)
+}
+
+/// Asks an already-running instance to show its window. Returns `true` if
+/// one acknowledged, in which case this process should exit.
+pub fn signal_existing(lock_path: &Path) -> bool {
+    let Some(port) = fs::read_to_string(lock_path)
+        .ok()
+        .and_then(|port| port.trim().parse::<u16>().ok())
+    else {
+        return false;
+    };
+
+    // A stale lock file may point at a port some other program now owns, so
+    // only trust an instance that answers the handshake
+    let handshake = || -> io::Result<bool> {
+        let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
+        let mut stream = TcpStream::connect_timeout(&addr, HANDSHAKE_TIMEOUT)?;
+        stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT))?;
+        stream.write_all(SHOW_REQUEST)?;
+
+        let mut reply = [0; SHOW_ACK.len()];
+        stream.read_exact(&mut reply)?;
+        Ok(reply == SHOW_ACK)
+    };
+    handshake().unwrap_or(false)
+}
+
+/// Claims the lock file and brings the main window to the front whenever a
+/// later launch signals us.
+pub fn listen(app_handle: AppHandle, lock_path: &Path) -> io::Result<()> {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+    if let Some(dir) = lock_path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(lock_path, listener.local_addr()?.port().to_string())?;
+
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            if let Err(e) = handle_request(&app_handle, stream) {
+                warn!("Ignoring invalid single-instance request: {}", e);
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Gives up the lock so a relaunched instance starts normally instead of
+/// handing over to this one.
+pub fn release(lock_path: &Path) {
+    if let Err(e) = fs::remove_file(lock_path) {
+        warn!("Failed to remove {}: {}", lock_path.display(), e);
+    }
+}
+
+fn handle_request(app_handle: &AppHandle, mut stream: TcpStream) -> io::Result<()> {
+    stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT))?;
+    let mut request = [0; SHOW_REQUEST.len()];
+    stream.read_exact(&mut request)?;
+    if request != SHOW_REQUEST {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "unexpected request"));
+    }
+    stream.write_all(SHOW_ACK)?;
+
+    info!("Another launch was requested, focusing the existing window");
+    if let Ok(window) = crate::main_window(app_handle) {
+        let _ = window.show();
+        let _ = window.set_focus();
+        if let Some(state) = crate::task_state(app_handle, "tracking the shown window") {
+            state.window_shown.store(true, Ordering::SeqCst);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,97 @@
+//! Streaming requests the UI can cancel, like model pulls and chats.
+
+use crate::error::CommandError;
+use std::collections::HashMap;
+use std::future::Future;
+use tokio::sync::Mutex;
+use tokio::task::AbortHandle;
+
+/// In-flight tasks by key, so they can be cancelled
+pub struct InFlight {
+    /// What the tasks are, e.g. "pull", for error messages
+    kind: &'static str,
+    tasks: Mutex<HashMap<String, AbortHandle>>,
+}
+
+impl InFlight {
+    pub fn new(kind: &'static str) -> Self {
+        InFlight {
+            kind,
+            tasks: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Runs `future` as the task for `key` and waits for it to finish, fail
+    /// or be cancelled. Only one task per key runs at a time.
+    pub async fn run<T, F>(&self, key: &str, future: F) -> Result<T, CommandError>
+    where
+        T: Send + 'static,
+        F: Future<Output = Result<T, CommandError>> + Send + 'static,
+    {
+        let task = {
+            let mut tasks = self.tasks.lock().await;
+            if tasks.contains_key(key) {
+                return Err(CommandError::Conflict(format!(
+                    "A {} for {} is already in progress",
+                    self.kind, key
+                )));
+            }
+            let task = tokio::spawn(future);
+            tasks.insert(key.to_string(), task.abort_handle());
+            task
+        };
+
+        let id = task.id();
+        let result = task.await;
+        // After a cancel the key may already belong to a newer task
+        {
+            let mut tasks = self.tasks.lock().await;
+            if tasks.get(key).is_some_and(|handle| handle.id() == id) {
+                tasks.remove(key);
+            }
+        }
+        match result {
+            Ok(result) => result,
+            Err(e) if e.is_cancelled() => Err(CommandError::Other(format!(
+                "The {} for {} was cancelled",
+                self.kind, key
+            ))),
+            Err(e) => Err(CommandError::Other(e.to_string())),
+        }
+    }
+
+    /// Aborts the task for `key`, which drops its request.
+    pub async fn cancel(&self, key: &str) -> Result<(), CommandError> {
+        match self.tasks.lock().await.remove(key) {
+            Some(handle) => {
+                handle.abort();
+                Ok(())
+            }
+            None => Err(CommandError::NotFound(format!(
+                "No {} in progress for {}",
+                self.kind, key
+            ))),
+        }
+    }
+}
+
+/// Feeds the body of `response` to `on_line` one line at a time, the last
+/// one possibly without a newline, until `on_line` returns `true`.
+pub async fn for_each_line(
+    mut response: reqwest::Response,
+    mut on_line: impl FnMut(&[u8]) -> Result<bool, CommandError>,
+) -> Result<(), CommandError> {
+    // Chunks don't line up with lines, so buffer until a newline arrives
+    let mut buffer = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        buffer.extend_from_slice(&chunk);
+        while let Some(newline) = buffer.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = buffer.drain(..=newline).collect();
+            if on_line(&line)? {
+                return Ok(());
+            }
+        }
+    }
+    on_line(&buffer)?;
+    Ok(())
+}
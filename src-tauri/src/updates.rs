@@ -0,0 +1,176 @@
+//! Tells users when a newer Ollama than the bundled one has been released,
+//! or when the backend is too old for this app.
+
+use crate::error::CommandError;
+use crate::{backend_base_url, bundled_ollama_version, fetch_ollama_version, AppState};
+use std::cmp::Ordering;
+use tracing::warn;
+
+const LATEST_RELEASE_URL: &str = "https://api.github.com/repos/ollama/ollama/releases/latest";
+/// Oldest backend whose API this app and its bundled frontend work with
+const MIN_BACKEND_VERSION: &str = "0.4.0";
+
+#[derive(serde::Serialize)]
+pub struct UpdateInfo {
+    current: String,
+    latest: String,
+    update_available: bool,
+}
+
+#[derive(serde::Deserialize)]
+struct Release {
+    tag_name: String,
+}
+
+/// Latest Ollama release tag, fetched at most once per launch to stay well
+/// clear of GitHub's unauthenticated rate limit. A failed fetch is kept too,
+/// so it isn't retried on every check.
+#[derive(Default)]
+pub struct LatestRelease(tokio::sync::OnceCell<Result<String, String>>);
+
+/// Compares the bundled Ollama (or, if its version wasn't recorded at build
+/// time, the running one) against the latest GitHub release.
+#[tauri::command]
+pub async fn check_ollama_update(
+    state: tauri::State<'_, AppState>,
+    latest: tauri::State<'_, LatestRelease>,
+) -> Result<UpdateInfo, CommandError> {
+    let current = match bundled_ollama_version() {
+        Some(version) => version.to_string(),
+        None => fetch_ollama_version(&state)
+            .await
+            .flatten()
+            .ok_or_else(|| "Could not determine the installed Ollama version".to_string())?,
+    };
+    let latest = latest
+        .0
+        .get_or_init(|| fetch_latest_release(&state.streaming_client))
+        .await
+        .clone()?;
+
+    let update_available = compare_versions(&latest, &current) == Some(Ordering::Greater);
+    Ok(UpdateInfo {
+        current,
+        latest,
+        update_available,
+    })
+}
+
+async fn fetch_latest_release(client: &reqwest::Client) -> Result<String, String> {
+    let response = client
+        .get(LATEST_RELEASE_URL)
+        // GitHub rejects API requests without a User-Agent
+        .header(reqwest::header::USER_AGENT, "open-webui-desktop")
+        .header(reqwest::header::ACCEPT, "application/vnd.github+json")
+        .send()
+        .await
+        .map_err(|e| format!("Could not reach GitHub: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("GitHub returned {} for the latest release", response.status()));
+    }
+    let release: Release = response
+        .json()
+        .await
+        .map_err(|e| format!("Unexpected response from GitHub: {}", e))?;
+    Ok(release.tag_name)
+}
+
+#[derive(serde::Serialize)]
+pub struct CompatResult {
+    /// False only for a backend known to be too old
+    compatible: bool,
+    /// `None` if the backend didn't report one
+    backend_version: Option<String>,
+    required: &'static str,
+}
+
+#[derive(serde::Deserialize)]
+struct BackendVersion {
+    version: Option<String>,
+}
+
+/// Compares the backend's `/api/version` against `MIN_BACKEND_VERSION`. A
+/// backend that doesn't report a version is let through with a warning.
+#[tauri::command]
+pub async fn check_backend_compatibility(
+    state: tauri::State<'_, AppState>,
+) -> Result<CompatResult, CommandError> {
+    let url = format!("{}/api/version", backend_base_url(&state.backend_url()));
+    let response = state
+        .http_client
+        .get(&url)
+        .send()
+        .await
+        .map_err(|e| {
+            CommandError::ServiceUnavailable(format!(
+                "The backend is not reachable at {}: {}",
+                url, e
+            ))
+        })?;
+    let backend_version = if response.status().is_success() {
+        response
+            .json::<BackendVersion>()
+            .await
+            .ok()
+            .and_then(|body| body.version)
+    } else {
+        None
+    };
+
+    let compatible = match backend_version
+        .as_deref()
+        .and_then(|version| compare_versions(version, MIN_BACKEND_VERSION))
+    {
+        Some(order) => order != Ordering::Less,
+        None => {
+            warn!("The backend did not report its version, assuming it is compatible");
+            true
+        }
+    };
+    if !compatible {
+        warn!(
+            "Backend {} is older than the required {}",
+            backend_version.as_deref().unwrap_or_default(),
+            MIN_BACKEND_VERSION
+        );
+    }
+    Ok(CompatResult {
+        compatible,
+        backend_version,
+        required: MIN_BACKEND_VERSION,
+    })
+}
+
+/// Parses `v0.5.7` or `0.5.7-rc1` into numeric components, ignoring any
+/// pre-release suffix.
+fn parse_version(version: &str) -> Option<Vec<u64>> {
+    let version = version.trim().trim_start_matches('v');
+    let release = version.split(['-', '+']).next()?;
+    release.split('.').map(|part| part.parse().ok()).collect()
+}
+
+/// Orders two versions, treating missing components as zero so `0.4` equals
+/// `0.4.0`. `None` if either doesn't parse.
+fn compare_versions(a: &str, b: &str) -> Option<Ordering> {
+    let (mut a, mut b) = (parse_version(a)?, parse_version(b)?);
+    let len = a.len().max(b.len());
+    a.resize(len, 0);
+    b.resize(len, 0);
+    Some(a.cmp(&b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn versions_compare_component_wise() {
+        assert_eq!(compare_versions("0.4", "0.4.0"), Some(Ordering::Equal));
+        assert_eq!(compare_versions("0.4.0", "0.4"), Some(Ordering::Equal));
+        assert_eq!(compare_versions("v0.5.7", "0.5"), Some(Ordering::Greater));
+        assert_eq!(compare_versions("0.3.10", "0.4.0"), Some(Ordering::Less));
+        assert_eq!(compare_versions("0.10.0", "0.9.9"), Some(Ordering::Greater));
+        assert_eq!(compare_versions("0.4.0-rc1", "0.4.0"), Some(Ordering::Equal));
+        assert_eq!(compare_versions("main", "0.4.0"), None);
+    }
+}